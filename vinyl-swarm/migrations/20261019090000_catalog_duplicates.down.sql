@@ -0,0 +1,11 @@
+-- Add down migration script here
+
+-- remove the duplicate finder indexes
+DROP INDEX IF EXISTS records_artist_trgm_idx;
+DROP INDEX IF EXISTS records_title_trgm_idx;
+
+DROP FUNCTION IF EXISTS normalize_catalog_text(TEXT);
+
+-- remove user roles
+ALTER TABLE users DROP CONSTRAINT IF EXISTS valid_user_role;
+ALTER TABLE users DROP COLUMN IF EXISTS user_role;
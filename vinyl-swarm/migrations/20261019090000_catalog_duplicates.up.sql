@@ -0,0 +1,33 @@
+-- Add up migration script here
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+CREATE EXTENSION IF NOT EXISTS unaccent;
+
+-- user roles: member, trusted, moderator, admin
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS user_role VARCHAR(20) NOT NULL DEFAULT 'member',
+    ADD CONSTRAINT valid_user_role CHECK (user_role IN ('member', 'trusted', 'moderator', 'admin'));
+
+-- the initial user runs the place
+UPDATE users SET user_role = 'admin' WHERE user_name = 'goodguygregory';
+
+-- normalize catalog strings for duplicate detection:
+-- lower case, strip diacritics, '&' -> 'and', drop punctuation and a leading 'the'
+CREATE OR REPLACE FUNCTION normalize_catalog_text(input TEXT)
+RETURNS TEXT AS $$
+    SELECT regexp_replace(
+        btrim(regexp_replace(
+            regexp_replace(
+                regexp_replace(
+                    replace(lower(unaccent('unaccent'::regdictionary, COALESCE(input, ''))), '&', ' and '),
+                    '[-/_]+', ' ', 'g'),
+                '[^a-z0-9 ]+', '', 'g'),
+            '\s+', ' ', 'g')),
+        '^the ', '')
+$$ LANGUAGE sql IMMUTABLE;
+
+-- trigram indexes used by the duplicate finder
+CREATE INDEX IF NOT EXISTS records_artist_trgm_idx
+    ON records USING GIN (normalize_catalog_text(artist) gin_trgm_ops);
+
+CREATE INDEX IF NOT EXISTS records_title_trgm_idx
+    ON records USING GIN (normalize_catalog_text(title) gin_trgm_ops);
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+-- delete the catalog_revisions table
+DROP TABLE IF EXISTS catalog_revisions CASCADE;
@@ -0,0 +1,19 @@
+-- Add up migration script here
+
+-- catalog_revisions table
+-- every edit to a record or record store is kept as a full snapshot plus a diff
+-- of the changed fields. revision 1 is the state before the first tracked edit.
+CREATE TABLE
+    IF NOT EXISTS catalog_revisions (
+        revision_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        entity_type VARCHAR(20) NOT NULL,
+        entity_id UUID NOT NULL,
+        revision_number INTEGER NOT NULL,
+        editor_id UUID REFERENCES users (user_id) ON DELETE SET NULL,
+        snapshot JSONB NOT NULL,
+        diff JSONB NOT NULL DEFAULT '{}'::jsonb,
+        reverted_to UUID REFERENCES catalog_revisions (revision_id) ON DELETE SET NULL,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        CONSTRAINT valid_revision_entity CHECK (entity_type IN ('record', 'record_store')),
+        CONSTRAINT unique_entity_revision UNIQUE (entity_type, entity_id, revision_number)
+    );
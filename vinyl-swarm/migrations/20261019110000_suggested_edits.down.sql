@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+-- delete the suggested_edits table
+DROP TABLE IF EXISTS suggested_edits CASCADE;
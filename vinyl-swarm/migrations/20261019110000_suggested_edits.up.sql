@@ -0,0 +1,22 @@
+-- Add up migration script here
+
+-- suggested_edits table
+-- edits to records and record stores from untrusted users wait here for a moderator
+CREATE TABLE
+    IF NOT EXISTS suggested_edits (
+        suggestion_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        entity_type VARCHAR(20) NOT NULL,
+        entity_id UUID NOT NULL,
+        submitter_id UUID REFERENCES users (user_id) ON DELETE SET NULL,
+        changes JSONB NOT NULL,
+        suggestion_status VARCHAR(20) NOT NULL DEFAULT 'pending',
+        reviewer_id UUID REFERENCES users (user_id) ON DELETE SET NULL,
+        rejection_reason TEXT,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        reviewed_at TIMESTAMP WITH TIME ZONE,
+        CONSTRAINT valid_suggestion_entity CHECK (entity_type IN ('record', 'record_store')),
+        CONSTRAINT valid_suggestion_status CHECK (suggestion_status IN ('pending', 'approved', 'rejected'))
+    );
+
+CREATE INDEX IF NOT EXISTS suggested_edits_status_idx ON suggested_edits (suggestion_status, created_at);
+CREATE INDEX IF NOT EXISTS suggested_edits_submitter_idx ON suggested_edits (submitter_id);
@@ -0,0 +1,9 @@
+-- Add down migration script here
+
+DROP INDEX IF EXISTS records_genre_idx;
+
+-- delete the genre_aliases table
+DROP TABLE IF EXISTS genre_aliases CASCADE;
+
+-- delete the genres table
+DROP TABLE IF EXISTS genres CASCADE;
@@ -0,0 +1,90 @@
+-- Add up migration script here
+
+-- genres table
+-- a genre may belong to a parent genre, e.g. Electronic -> Trip Hop
+CREATE TABLE
+    IF NOT EXISTS genres (
+        genre_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        genre_name VARCHAR(100) NOT NULL UNIQUE,
+        parent_genre_id UUID REFERENCES genres (genre_id) ON DELETE SET NULL
+    );
+
+-- genre_aliases table
+-- aliases are stored normalized (see normalize_catalog_text) and map any spelling
+-- of a genre onto its canonical name
+CREATE TABLE
+    IF NOT EXISTS genre_aliases (
+        alias VARCHAR(100) PRIMARY KEY NOT NULL,
+        genre_id UUID NOT NULL REFERENCES genres (genre_id) ON DELETE CASCADE
+    );
+
+-- populate the taxonomy
+
+-- top level genres
+INSERT INTO genres (genre_name)
+VALUES
+    ('Electronic'), ('Jazz'), ('Rock'), ('Pop'), ('Funk'), ('R&B'), ('Reggae'), ('Hip Hop');
+
+-- sub-genres
+INSERT INTO genres (genre_name, parent_genre_id)
+SELECT sub.genre_name, parent.genre_id
+FROM (
+    VALUES
+        ('Trip Hop', 'Electronic'),
+        ('Ambient', 'Electronic'),
+        ('Downtempo', 'Electronic'),
+        ('House', 'Electronic'),
+        ('Folktronica', 'Electronic'),
+        ('Indie Electronic', 'Electronic'),
+        ('Jazz Fusion', 'Jazz'),
+        ('Jazz-Funk', 'Jazz'),
+        ('Psychedelic Rock', 'Rock'),
+        ('Surf Rock', 'Rock'),
+        ('Synth-Pop', 'Pop'),
+        ('Psychedelic Pop', 'Pop'),
+        ('Dub', 'Reggae')
+) AS sub (genre_name, parent_name)
+JOIN genres parent ON parent.genre_name = sub.parent_name;
+
+-- every canonical name is its own alias
+INSERT INTO genre_aliases (alias, genre_id)
+SELECT normalize_catalog_text(genre_name), genre_id FROM genres;
+
+-- common alternative spellings
+INSERT INTO genre_aliases (alias, genre_id)
+SELECT alias.alias, genres.genre_id
+FROM (
+    VALUES
+        ('electronica', 'Electronic'),
+        ('electronic music', 'Electronic'),
+        ('triphop', 'Trip Hop'),
+        ('synthpop', 'Synth-Pop'),
+        ('jazzfunk', 'Jazz-Funk'),
+        ('fusion', 'Jazz Fusion'),
+        ('hiphop', 'Hip Hop'),
+        ('rap', 'Hip Hop'),
+        ('rnb', 'R&B'),
+        ('r and b', 'R&B'),
+        ('rhythm and blues', 'R&B'),
+        ('psych rock', 'Psychedelic Rock'),
+        ('psych pop', 'Psychedelic Pop'),
+        ('chillout', 'Downtempo')
+) AS alias (alias, genre_name)
+JOIN genres ON genres.genre_name = alias.genre_name
+ON CONFLICT (alias) DO NOTHING;
+
+-- rewrite existing records onto the canonical names, keeping their order
+UPDATE records r SET genre = (
+    SELECT COALESCE(array_agg(canonical.genre_name ORDER BY canonical.position), '{}')
+    FROM (
+        SELECT COALESCE(g.genre_name, btrim(supplied.genre_name)) AS genre_name,
+            MIN(supplied.position) AS position
+        FROM unnest(r.genre) WITH ORDINALITY AS supplied (genre_name, position)
+        LEFT JOIN genre_aliases a ON a.alias = normalize_catalog_text(supplied.genre_name)
+        LEFT JOIN genres g ON g.genre_id = a.genre_id
+        GROUP BY 1
+    ) canonical
+)
+WHERE r.genre IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS records_genre_idx ON records USING GIN (genre);
@@ -0,0 +1,8 @@
+-- Add down migration script here
+
+-- remove the record_stores coordinates
+ALTER TABLE record_stores
+    DROP CONSTRAINT IF EXISTS valid_store_latitude,
+    DROP CONSTRAINT IF EXISTS valid_store_longitude,
+    DROP COLUMN IF EXISTS latitude,
+    DROP COLUMN IF EXISTS longitude;
@@ -0,0 +1,18 @@
+-- Add up migration script here
+
+-- record_stores coordinates for "near me" searches
+ALTER TABLE record_stores
+    ADD COLUMN IF NOT EXISTS latitude DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS longitude DOUBLE PRECISION,
+    ADD CONSTRAINT valid_store_latitude CHECK (latitude BETWEEN -90 AND 90),
+    ADD CONSTRAINT valid_store_longitude CHECK (longitude BETWEEN -180 AND 180);
+
+-- place the initial record stores
+UPDATE record_stores SET latitude = 38.2559, longitude = -85.7163
+WHERE store_name = 'Guestroom Records' AND store_zip = '40206';
+
+UPDATE record_stores SET latitude = 45.5228, longitude = -122.6326
+WHERE store_name = 'Music Millennium' AND store_zip = '97214';
+
+UPDATE record_stores SET latitude = 35.5787, longitude = -82.5897
+WHERE store_name = 'Harvest Records' AND store_zip = '28806';
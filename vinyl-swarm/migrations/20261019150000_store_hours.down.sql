@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+-- remove opening hours and the store time zones
+DROP FUNCTION IF EXISTS store_is_open (UUID, TIMESTAMP WITH TIME ZONE);
+DROP TABLE IF EXISTS store_hour_exceptions CASCADE;
+DROP TABLE IF EXISTS store_hours CASCADE;
+ALTER TABLE record_stores DROP COLUMN IF EXISTS time_zone;
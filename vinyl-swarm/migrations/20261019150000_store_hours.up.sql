@@ -0,0 +1,73 @@
+-- Add up migration script here
+
+-- record_stores keep their local time zone so opening hours can be compared to now()
+ALTER TABLE record_stores
+    ADD COLUMN IF NOT EXISTS time_zone TEXT NOT NULL DEFAULT 'America/New_York';
+
+UPDATE record_stores SET time_zone = 'America/Kentucky/Louisville'
+WHERE store_name = 'Guestroom Records' AND store_zip = '40206';
+
+UPDATE record_stores SET time_zone = 'America/Los_Angeles'
+WHERE store_name = 'Music Millennium' AND store_zip = '97214';
+
+-- store_hours table
+-- weekly opening windows in the store's local time, day_of_week follows
+-- EXTRACT(DOW): 0 is sunday. a closing time at or before the opening time runs past midnight
+CREATE TABLE
+    IF NOT EXISTS store_hours (
+        record_store_id UUID NOT NULL REFERENCES record_stores (record_store_id) ON DELETE CASCADE,
+        day_of_week SMALLINT NOT NULL,
+        opens_at TIME NOT NULL,
+        closes_at TIME NOT NULL,
+        PRIMARY KEY (record_store_id, day_of_week, opens_at),
+        CONSTRAINT valid_day_of_week CHECK (day_of_week BETWEEN 0 AND 6)
+    );
+
+-- store_hour_exceptions table
+-- holidays and one-off hours replacing a single day's weekly hours,
+-- no opening time means the store is closed that day
+CREATE TABLE
+    IF NOT EXISTS store_hour_exceptions (
+        record_store_id UUID NOT NULL REFERENCES record_stores (record_store_id) ON DELETE CASCADE,
+        exception_date DATE NOT NULL,
+        opens_at TIME,
+        closes_at TIME,
+        note TEXT,
+        PRIMARY KEY (record_store_id, exception_date),
+        CONSTRAINT complete_exception_hours CHECK ((opens_at IS NULL) = (closes_at IS NULL))
+    );
+
+-- store_is_open
+-- whether a store is open at a point in time, checking the local day's hours
+-- and the previous day's in case they run past midnight
+CREATE OR REPLACE FUNCTION store_is_open(store_id UUID, at_time TIMESTAMP WITH TIME ZONE)
+RETURNS BOOLEAN LANGUAGE sql STABLE AS $$
+    WITH local_time AS (
+        SELECT at_time AT TIME ZONE time_zone AS local_ts
+        FROM record_stores WHERE record_store_id = store_id
+    ),
+    local_days AS (
+        SELECT local_ts, local_ts::date - offset_days AS local_day
+        FROM local_time, (VALUES (0), (1)) AS offsets (offset_days)
+    ),
+    opening_windows AS (
+        SELECT d.local_ts, d.local_day, e.opens_at, e.closes_at
+        FROM local_days d
+        JOIN store_hour_exceptions e ON e.record_store_id = store_id AND e.exception_date = d.local_day
+        UNION ALL
+        SELECT d.local_ts, d.local_day, h.opens_at, h.closes_at
+        FROM local_days d
+        JOIN store_hours h ON h.record_store_id = store_id AND h.day_of_week = EXTRACT(DOW FROM d.local_day)
+        WHERE NOT EXISTS (
+            SELECT 1 FROM store_hour_exceptions e
+            WHERE e.record_store_id = store_id AND e.exception_date = d.local_day
+        )
+    )
+    SELECT COALESCE(bool_or(
+        local_ts >= local_day + opens_at
+        AND local_ts < local_day + closes_at
+            + CASE WHEN closes_at <= opens_at THEN INTERVAL '1 day' ELSE INTERVAL '0' END
+    ), FALSE)
+    FROM opening_windows
+    WHERE opens_at IS NOT NULL
+$$;
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+-- delete the store_inventory table
+DROP TABLE IF EXISTS store_inventory CASCADE;
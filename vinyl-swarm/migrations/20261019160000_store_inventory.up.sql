@@ -0,0 +1,24 @@
+-- Add up migration script here
+
+-- store_inventory table
+-- copies of catalog records for sale at a record store, listed by the store or
+-- reported by users who spotted them in the bins. one listing per record and
+-- condition, graded on the goldmine scale
+CREATE TABLE
+    IF NOT EXISTS store_inventory (
+        listing_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        record_store_id UUID NOT NULL REFERENCES record_stores (record_store_id) ON DELETE CASCADE,
+        record_id UUID NOT NULL REFERENCES records (record_id) ON DELETE CASCADE,
+        price DECIMAL(10,2) NOT NULL,
+        condition VARCHAR(4) NOT NULL,
+        quantity INTEGER NOT NULL DEFAULT 1,
+        reported_by UUID REFERENCES users (user_id) ON DELETE SET NULL,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        CONSTRAINT unique_store_listing UNIQUE (record_store_id, record_id, condition),
+        CONSTRAINT valid_listing_condition CHECK (condition IN ('M', 'NM', 'VG+', 'VG', 'G+', 'G', 'F', 'P')),
+        CONSTRAINT valid_listing_price CHECK (price >= 0),
+        CONSTRAINT valid_listing_quantity CHECK (quantity >= 0)
+    );
+
+CREATE INDEX IF NOT EXISTS store_inventory_record_idx ON store_inventory (record_id);
@@ -0,0 +1,8 @@
+-- Add down migration script here
+
+-- remove wishlist alerts and their preferences
+DROP TABLE IF EXISTS wishlist_alerts CASCADE;
+ALTER TABLE users DROP COLUMN IF EXISTS alert_favorite_stores_only;
+ALTER TABLE user_wishlist
+    DROP CONSTRAINT IF EXISTS valid_target_price,
+    DROP COLUMN IF EXISTS target_price;
@@ -0,0 +1,26 @@
+-- Add up migration script here
+
+-- the most a user will pay for a wishlist record, NULL alerts at any price
+ALTER TABLE user_wishlist
+    ADD COLUMN IF NOT EXISTS target_price DECIMAL(10,2),
+    ADD CONSTRAINT valid_target_price CHECK (target_price >= 0);
+
+-- users can limit wishlist alerts to the stores in user_record_stores
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS alert_favorite_stores_only BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- wishlist_alerts table
+-- a store listing matching a user's wishlist, the price is what the record was
+-- listed for when the alert went out. a later price drop renews the alert
+CREATE TABLE
+    IF NOT EXISTS wishlist_alerts (
+        alert_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        user_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        listing_id UUID NOT NULL REFERENCES store_inventory (listing_id) ON DELETE CASCADE,
+        price DECIMAL(10,2) NOT NULL,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        read_at TIMESTAMP WITH TIME ZONE,
+        CONSTRAINT unique_listing_alert UNIQUE (user_id, listing_id)
+    );
+
+CREATE INDEX IF NOT EXISTS wishlist_alerts_user_idx ON wishlist_alerts (user_id, created_at);
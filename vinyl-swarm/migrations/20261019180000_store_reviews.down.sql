@@ -0,0 +1,8 @@
+-- Add down migration script here
+
+-- delete the store_reviews table and the store ratings
+DROP TABLE IF EXISTS store_reviews CASCADE;
+DROP FUNCTION IF EXISTS refresh_store_rating ();
+ALTER TABLE record_stores
+    DROP COLUMN IF EXISTS average_rating,
+    DROP COLUMN IF EXISTS review_count;
@@ -0,0 +1,56 @@
+-- Add up migration script here
+
+-- store_reviews table
+-- one review per user per store, rated 1 to 5 stars overall and optionally
+-- for the selection, prices and staff
+CREATE TABLE
+    IF NOT EXISTS store_reviews (
+        review_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        record_store_id UUID NOT NULL REFERENCES record_stores (record_store_id) ON DELETE CASCADE,
+        user_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        rating SMALLINT NOT NULL,
+        review_text TEXT,
+        visited_on DATE,
+        selection_rating SMALLINT,
+        price_rating SMALLINT,
+        staff_rating SMALLINT,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        CONSTRAINT unique_store_review UNIQUE (record_store_id, user_id),
+        CONSTRAINT valid_store_rating CHECK (rating BETWEEN 1 AND 5),
+        CONSTRAINT valid_selection_rating CHECK (selection_rating BETWEEN 1 AND 5),
+        CONSTRAINT valid_price_rating CHECK (price_rating BETWEEN 1 AND 5),
+        CONSTRAINT valid_staff_rating CHECK (staff_rating BETWEEN 1 AND 5)
+    );
+
+CREATE INDEX IF NOT EXISTS store_reviews_store_idx ON store_reviews (record_store_id, created_at);
+
+-- record_stores carry their rating so every store response can show it
+ALTER TABLE record_stores
+    ADD COLUMN IF NOT EXISTS average_rating DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS review_count INTEGER NOT NULL DEFAULT 0;
+
+-- refresh_store_rating
+-- keeps record_stores.average_rating and review_count in step with store_reviews
+CREATE OR REPLACE FUNCTION refresh_store_rating() RETURNS TRIGGER LANGUAGE plpgsql AS $$
+DECLARE
+    store_id UUID;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        store_id := OLD.record_store_id;
+    ELSE
+        store_id := NEW.record_store_id;
+    END IF;
+
+    UPDATE record_stores SET
+        average_rating = (SELECT ROUND(AVG(rating), 2)::DOUBLE PRECISION FROM store_reviews WHERE record_store_id = store_id),
+        review_count = (SELECT COUNT(*) FROM store_reviews WHERE record_store_id = store_id)
+    WHERE record_store_id = store_id;
+
+    RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER store_reviews_rating
+AFTER INSERT OR UPDATE OF rating OR DELETE ON store_reviews
+FOR EACH ROW EXECUTE FUNCTION refresh_store_rating();
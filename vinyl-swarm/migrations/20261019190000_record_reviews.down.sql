@@ -0,0 +1,9 @@
+-- Add down migration script here
+
+-- delete the record_reviews table and the record ratings
+DROP TABLE IF EXISTS record_reviews CASCADE;
+DROP FUNCTION IF EXISTS refresh_record_rating ();
+DROP INDEX IF EXISTS records_rating_idx;
+ALTER TABLE records
+    DROP COLUMN IF EXISTS average_rating,
+    DROP COLUMN IF EXISTS review_count;
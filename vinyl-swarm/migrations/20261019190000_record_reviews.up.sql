@@ -0,0 +1,53 @@
+-- Add up migration script here
+
+-- record_reviews table
+-- one review per user per record, rated 1 to 5 stars
+CREATE TABLE
+    IF NOT EXISTS record_reviews (
+        review_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        record_id UUID NOT NULL REFERENCES records (record_id) ON DELETE CASCADE,
+        user_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        rating SMALLINT NOT NULL,
+        review_text TEXT,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        CONSTRAINT unique_record_review UNIQUE (record_id, user_id),
+        CONSTRAINT valid_record_rating CHECK (rating BETWEEN 1 AND 5)
+    );
+
+CREATE INDEX IF NOT EXISTS record_reviews_record_idx ON record_reviews (record_id, created_at);
+
+-- records carry their rating so every record response can show it
+ALTER TABLE records
+    ADD COLUMN IF NOT EXISTS average_rating DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS review_count INTEGER NOT NULL DEFAULT 0;
+
+-- refresh_record_rating
+-- keeps records.average_rating and review_count in step with record_reviews,
+-- for both records when a merge moves reviews from one record to another
+CREATE OR REPLACE FUNCTION refresh_record_rating() RETURNS TRIGGER LANGUAGE plpgsql AS $$
+DECLARE
+    affected_ids UUID[];
+BEGIN
+    IF TG_OP = 'INSERT' THEN
+        affected_ids := ARRAY[NEW.record_id];
+    ELSIF TG_OP = 'DELETE' THEN
+        affected_ids := ARRAY[OLD.record_id];
+    ELSE
+        affected_ids := ARRAY[OLD.record_id, NEW.record_id];
+    END IF;
+
+    UPDATE records SET
+        average_rating = (SELECT ROUND(AVG(rating), 2)::DOUBLE PRECISION FROM record_reviews rr WHERE rr.record_id = records.record_id),
+        review_count = (SELECT COUNT(*) FROM record_reviews rr WHERE rr.record_id = records.record_id)
+    WHERE record_id = ANY(affected_ids);
+
+    RETURN NULL;
+END;
+$$;
+
+CREATE TRIGGER record_reviews_rating
+AFTER INSERT OR UPDATE OF rating, record_id OR DELETE ON record_reviews
+FOR EACH ROW EXECUTE FUNCTION refresh_record_rating();
+
+CREATE INDEX IF NOT EXISTS records_rating_idx ON records (average_rating DESC NULLS LAST);
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+-- delete the store events tables
+DROP TABLE IF EXISTS store_event_records;
+DROP TABLE IF EXISTS store_events;
@@ -0,0 +1,31 @@
+-- Add up migration script here
+
+-- store_events table
+-- record store day, in-stores, swap meets and the like, times are stored in UTC
+-- and ends_at is optional for events without a set finish
+CREATE TABLE
+    IF NOT EXISTS store_events (
+        event_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        record_store_id UUID NOT NULL REFERENCES record_stores (record_store_id) ON DELETE CASCADE,
+        title TEXT NOT NULL,
+        description TEXT,
+        event_type TEXT NOT NULL DEFAULT 'other',
+        starts_at TIMESTAMP WITH TIME ZONE NOT NULL,
+        ends_at TIMESTAMP WITH TIME ZONE,
+        created_by UUID REFERENCES users (user_id) ON DELETE SET NULL,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        CONSTRAINT valid_event_type CHECK (event_type IN ('record_store_day', 'in_store', 'swap_meet', 'listening_party', 'other')),
+        CONSTRAINT valid_event_times CHECK (ends_at IS NULL OR ends_at > starts_at)
+    );
+
+CREATE INDEX IF NOT EXISTS store_events_store_idx ON store_events (record_store_id, starts_at);
+
+-- store_event_records table
+-- records tied to an event, like record store day exclusives or the album an in-store is for
+CREATE TABLE
+    IF NOT EXISTS store_event_records (
+        event_id UUID NOT NULL REFERENCES store_events (event_id) ON DELETE CASCADE,
+        record_id UUID NOT NULL REFERENCES records (record_id) ON DELETE CASCADE,
+        PRIMARY KEY (event_id, record_id)
+    );
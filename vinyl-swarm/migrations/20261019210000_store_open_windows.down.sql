@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+-- delete the store_open_windows function
+DROP FUNCTION IF EXISTS store_open_windows (UUID, TIMESTAMP WITH TIME ZONE, TIMESTAMP WITH TIME ZONE);
@@ -0,0 +1,45 @@
+-- Add up migration script here
+
+-- store_open_windows
+-- a store's opening windows as points in time that overlap from_time to to_time,
+-- using the same exceptions and past-midnight rules as store_is_open
+CREATE OR REPLACE FUNCTION store_open_windows(
+    store_id UUID,
+    from_time TIMESTAMP WITH TIME ZONE,
+    to_time TIMESTAMP WITH TIME ZONE
+)
+RETURNS TABLE (window_opens TIMESTAMP WITH TIME ZONE, window_closes TIMESTAMP WITH TIME ZONE)
+LANGUAGE sql STABLE AS $$
+    WITH local_days AS (
+        SELECT local_day::date AS local_day, s.time_zone
+        FROM record_stores s, generate_series(
+            (from_time AT TIME ZONE s.time_zone)::date - 1,
+            (to_time AT TIME ZONE s.time_zone)::date,
+            INTERVAL '1 day'
+        ) AS local_day
+        WHERE s.record_store_id = store_id
+    ),
+    opening_windows AS (
+        SELECT d.local_day, d.time_zone, e.opens_at, e.closes_at
+        FROM local_days d
+        JOIN store_hour_exceptions e ON e.record_store_id = store_id AND e.exception_date = d.local_day
+        UNION ALL
+        SELECT d.local_day, d.time_zone, h.opens_at, h.closes_at
+        FROM local_days d
+        JOIN store_hours h ON h.record_store_id = store_id AND h.day_of_week = EXTRACT(DOW FROM d.local_day)
+        WHERE NOT EXISTS (
+            SELECT 1 FROM store_hour_exceptions e
+            WHERE e.record_store_id = store_id AND e.exception_date = d.local_day
+        )
+    )
+    SELECT window_opens, window_closes FROM (
+        SELECT (local_day + opens_at) AT TIME ZONE time_zone AS window_opens,
+            (local_day + closes_at
+                + CASE WHEN closes_at <= opens_at THEN INTERVAL '1 day' ELSE INTERVAL '0' END
+            ) AT TIME ZONE time_zone AS window_closes
+        FROM opening_windows
+        WHERE opens_at IS NOT NULL
+    ) AS windows
+    WHERE window_closes > from_time AND window_opens < to_time
+    ORDER BY window_opens
+$$;
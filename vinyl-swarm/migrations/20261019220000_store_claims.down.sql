@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+-- delete the store_claims table
+DROP TABLE IF EXISTS store_claims;
@@ -0,0 +1,24 @@
+-- Add up migration script here
+
+-- store_claims table
+-- a user claiming to run a record store. an admin checks the evidence by hand and
+-- verifies the claim, after which the user manages the store as one of its owners
+CREATE TABLE
+    IF NOT EXISTS store_claims (
+        claim_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        record_store_id UUID NOT NULL REFERENCES record_stores (record_store_id) ON DELETE CASCADE,
+        user_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        evidence TEXT NOT NULL,
+        claim_status VARCHAR(20) NOT NULL DEFAULT 'pending',
+        reviewer_id UUID REFERENCES users (user_id) ON DELETE SET NULL,
+        rejection_reason TEXT,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        reviewed_at TIMESTAMP WITH TIME ZONE,
+        CONSTRAINT valid_claim_status CHECK (claim_status IN ('pending', 'verified', 'rejected', 'revoked'))
+    );
+
+-- a user has at most one open claim on a store, rejected and revoked ones stay as history
+CREATE UNIQUE INDEX IF NOT EXISTS store_claims_open_idx ON store_claims (record_store_id, user_id)
+WHERE claim_status IN ('pending', 'verified');
+
+CREATE INDEX IF NOT EXISTS store_claims_status_idx ON store_claims (claim_status, created_at);
@@ -0,0 +1,6 @@
+-- Add down migration script here
+
+-- delete the store address normalization
+DROP INDEX IF EXISTS record_stores_address_idx;
+DROP FUNCTION IF EXISTS normalize_store_address (TEXT);
+DROP FUNCTION IF EXISTS normalize_store_zip (TEXT);
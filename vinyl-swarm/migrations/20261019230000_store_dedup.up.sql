@@ -0,0 +1,39 @@
+-- Add up migration script here
+
+-- normalize street addresses for duplicate store detection:
+-- lower case, strip diacritics, drop suite/unit/apartment numbers and punctuation,
+-- and shorten street types and directions to their USPS abbreviations.
+-- a designator only goes along with a number or single letter after it, so
+-- streets like Floor Ave keep their name
+CREATE OR REPLACE FUNCTION normalize_store_address(input TEXT)
+RETURNS TEXT AS $$
+    SELECT COALESCE(string_agg(COALESCE(abbreviations.short_form, words.word), ' ' ORDER BY words.position), '')
+    FROM regexp_split_to_table(
+        btrim(regexp_replace(
+            regexp_replace(
+                regexp_replace(
+                    lower(unaccent('unaccent'::regdictionary, COALESCE(input, ''))),
+                    '(\m(suite|ste|unit|apt|apartment|floor|fl|room|rm)\M\.?|#)\s*([0-9][a-z0-9-]*|[a-z]\M)', ' ', 'g'),
+                '[^a-z0-9 ]+', ' ', 'g'),
+            '\s+', ' ', 'g')),
+        ' '
+    ) WITH ORDINALITY AS words (word, position)
+    LEFT JOIN (VALUES
+        ('avenue', 'ave'), ('av', 'ave'), ('street', 'st'), ('road', 'rd'),
+        ('boulevard', 'blvd'), ('drive', 'dr'), ('lane', 'ln'), ('court', 'ct'),
+        ('place', 'pl'), ('highway', 'hwy'), ('parkway', 'pkwy'), ('square', 'sq'),
+        ('terrace', 'ter'), ('circle', 'cir'), ('trail', 'trl'),
+        ('north', 'n'), ('south', 's'), ('east', 'e'), ('west', 'w'),
+        ('northeast', 'ne'), ('northwest', 'nw'), ('southeast', 'se'), ('southwest', 'sw')
+    ) AS abbreviations (long_form, short_form) ON abbreviations.long_form = words.word
+    WHERE words.word <> ''
+$$ LANGUAGE sql IMMUTABLE;
+
+-- the 5 digit zip code, so zip+4 codes match their zip code
+CREATE OR REPLACE FUNCTION normalize_store_zip(input TEXT)
+RETURNS TEXT AS $$
+    SELECT left(regexp_replace(COALESCE(input, ''), '[^0-9]', '', 'g'), 5)
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE INDEX IF NOT EXISTS record_stores_address_idx
+    ON record_stores (normalize_store_address(store_address), normalize_store_zip(store_zip));
@@ -0,0 +1,7 @@
+-- Add down migration script here
+
+-- delete the activity feed tables and privacy setting
+DROP TABLE IF EXISTS activity_events;
+DROP TABLE IF EXISTS user_follows;
+ALTER TABLE users
+    DROP COLUMN IF EXISTS activity_visibility;
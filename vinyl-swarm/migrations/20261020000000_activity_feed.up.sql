@@ -0,0 +1,35 @@
+-- Add up migration script here
+
+-- who can see a user's activity: anyone, only the people following them, or nobody
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS activity_visibility VARCHAR(20) NOT NULL DEFAULT 'followers',
+    ADD CONSTRAINT valid_activity_visibility CHECK (activity_visibility IN ('public', 'followers', 'private'));
+
+-- user_follows table
+CREATE TABLE
+    IF NOT EXISTS user_follows (
+        follower_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        followed_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        PRIMARY KEY (follower_id, followed_id),
+        CONSTRAINT no_self_follow CHECK (follower_id <> followed_id)
+    );
+
+CREATE INDEX IF NOT EXISTS user_follows_followed_idx ON user_follows (followed_id);
+
+-- activity_events table
+-- what users did, written by the collection, wishlist, favorite store and review handlers.
+-- review_id points into store_reviews or record_reviews depending on the activity_type
+CREATE TABLE
+    IF NOT EXISTS activity_events (
+        activity_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        user_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        activity_type VARCHAR(20) NOT NULL,
+        record_id UUID REFERENCES records (record_id) ON DELETE CASCADE,
+        record_store_id UUID REFERENCES record_stores (record_store_id) ON DELETE CASCADE,
+        review_id UUID,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        CONSTRAINT valid_activity_type CHECK (activity_type IN ('collected', 'wished', 'favorited_store', 'reviewed_record', 'reviewed_store'))
+    );
+
+CREATE INDEX IF NOT EXISTS activity_events_user_idx ON activity_events (user_id, created_at DESC);
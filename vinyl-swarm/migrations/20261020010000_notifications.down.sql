@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+-- delete the notification inbox and preferences
+DROP TABLE IF EXISTS notification_preferences;
+DROP TABLE IF EXISTS notifications;
@@ -0,0 +1,29 @@
+-- Add up migration script here
+
+-- notifications table
+-- the in-app inbox. actor_id is whoever caused the notification, if anyone, and
+-- subject_id is what it's about: a wishlist alert, a store claim, a follower
+CREATE TABLE
+    IF NOT EXISTS notifications (
+        notification_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        user_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        notification_type VARCHAR(30) NOT NULL,
+        message TEXT NOT NULL,
+        actor_id UUID REFERENCES users (user_id) ON DELETE SET NULL,
+        subject_id UUID,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        read_at TIMESTAMP WITH TIME ZONE,
+        CONSTRAINT valid_notification_type CHECK (notification_type IN ('new_follower', 'wishlist_match', 'store_claim'))
+    );
+
+CREATE INDEX IF NOT EXISTS notifications_user_idx ON notifications (user_id, created_at DESC);
+
+-- notification_preferences table
+-- the types of notification a user has turned on or off, every type is on without a row
+CREATE TABLE
+    IF NOT EXISTS notification_preferences (
+        user_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        notification_type VARCHAR(30) NOT NULL,
+        enabled BOOLEAN NOT NULL,
+        PRIMARY KEY (user_id, notification_type)
+    );
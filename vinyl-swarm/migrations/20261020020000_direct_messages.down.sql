@@ -0,0 +1,11 @@
+-- Add down migration script here
+
+-- delete direct messaging and blocks
+DELETE FROM notifications WHERE notification_type = 'direct_message';
+DELETE FROM notification_preferences WHERE notification_type = 'direct_message';
+ALTER TABLE notifications
+    DROP CONSTRAINT IF EXISTS valid_notification_type,
+    ADD CONSTRAINT valid_notification_type CHECK (notification_type IN ('new_follower', 'wishlist_match', 'store_claim'));
+DROP TABLE IF EXISTS direct_messages;
+DROP TABLE IF EXISTS conversations;
+DROP TABLE IF EXISTS user_blocks;
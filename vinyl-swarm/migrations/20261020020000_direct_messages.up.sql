@@ -0,0 +1,50 @@
+-- Add up migration script here
+
+-- user_blocks table
+-- a block works both ways: neither user can follow or message the other
+CREATE TABLE
+    IF NOT EXISTS user_blocks (
+        blocker_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        blocked_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        PRIMARY KEY (blocker_id, blocked_id),
+        CONSTRAINT no_self_block CHECK (blocker_id <> blocked_id)
+    );
+
+CREATE INDEX IF NOT EXISTS user_blocks_blocked_idx ON user_blocks (blocked_id);
+
+-- conversations table
+-- a one-to-one thread, the pair is stored lowest user_id first so it's only started once
+CREATE TABLE
+    IF NOT EXISTS conversations (
+        conversation_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        user_one_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        user_two_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        last_message_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        CONSTRAINT unique_conversation UNIQUE (user_one_id, user_two_id),
+        CONSTRAINT ordered_participants CHECK (user_one_id < user_two_id)
+    );
+
+CREATE INDEX IF NOT EXISTS conversations_user_two_idx ON conversations (user_two_id);
+
+-- direct_messages table
+-- record_id attaches a record to talk about, read_at is when the recipient read it
+CREATE TABLE
+    IF NOT EXISTS direct_messages (
+        message_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        conversation_id UUID NOT NULL REFERENCES conversations (conversation_id) ON DELETE CASCADE,
+        sender_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        body TEXT NOT NULL,
+        record_id UUID REFERENCES records (record_id) ON DELETE SET NULL,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        read_at TIMESTAMP WITH TIME ZONE,
+        CONSTRAINT message_not_empty CHECK (length(trim(body)) > 0)
+    );
+
+CREATE INDEX IF NOT EXISTS direct_messages_conversation_idx ON direct_messages (conversation_id, created_at DESC);
+
+-- new messages are a type of notification
+ALTER TABLE notifications
+    DROP CONSTRAINT IF EXISTS valid_notification_type,
+    ADD CONSTRAINT valid_notification_type CHECK (notification_type IN ('new_follower', 'wishlist_match', 'store_claim', 'direct_message'));
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+-- delete the recommendation job's similarity table
+DROP TABLE IF EXISTS record_similarity;
@@ -0,0 +1,16 @@
+-- Add up migration script here
+
+-- record_similarity table
+-- item-to-item similarity rebuilt by the recommendation job from records users own
+-- or wish for together. score is the cosine similarity of the two records' collectors
+-- and co_collectors how many users have both. only each record's closest neighbours are kept
+CREATE TABLE
+    IF NOT EXISTS record_similarity (
+        record_id UUID NOT NULL REFERENCES records (record_id) ON DELETE CASCADE,
+        similar_record_id UUID NOT NULL REFERENCES records (record_id) ON DELETE CASCADE,
+        score DOUBLE PRECISION NOT NULL,
+        co_collectors INTEGER NOT NULL,
+        computed_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        PRIMARY KEY (record_id, similar_record_id),
+        CONSTRAINT no_self_similarity CHECK (record_id <> similar_record_id)
+    );
@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+-- delete user curated record lists
+DROP TABLE IF EXISTS record_list_items;
+DROP TABLE IF EXISTS record_lists;
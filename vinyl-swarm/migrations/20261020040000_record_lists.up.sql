@@ -0,0 +1,36 @@
+-- Add up migration script here
+
+-- record_lists table
+-- named, ordered lists of records a user curates. public lists show on the user's
+-- profile and can be copied, unlisted ones are only reachable by their list_id and
+-- private ones only by their owner. copied_from_list_id is the list it was copied from
+CREATE TABLE
+    IF NOT EXISTS record_lists (
+        list_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        user_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        title VARCHAR(100) NOT NULL,
+        description TEXT,
+        visibility VARCHAR(20) NOT NULL DEFAULT 'private',
+        copied_from_list_id UUID REFERENCES record_lists (list_id) ON DELETE SET NULL,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        updated_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        CONSTRAINT valid_list_visibility CHECK (visibility IN ('public', 'unlisted', 'private')),
+        CONSTRAINT list_title_not_empty CHECK (length(trim(title)) > 0)
+    );
+
+CREATE INDEX IF NOT EXISTS record_lists_user_idx ON record_lists (user_id);
+
+-- record_list_items table
+-- a record can only be on a list once, position orders the list starting from 1.
+-- the position constraint is deferred so a list can be reordered in one go
+CREATE TABLE
+    IF NOT EXISTS record_list_items (
+        list_id UUID NOT NULL REFERENCES record_lists (list_id) ON DELETE CASCADE,
+        record_id UUID NOT NULL REFERENCES records (record_id) ON DELETE CASCADE,
+        position INTEGER NOT NULL,
+        note TEXT,
+        added_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        PRIMARY KEY (list_id, record_id),
+        CONSTRAINT unique_list_position UNIQUE (list_id, position) DEFERRABLE INITIALLY DEFERRED,
+        CONSTRAINT valid_list_position CHECK (position > 0)
+    );
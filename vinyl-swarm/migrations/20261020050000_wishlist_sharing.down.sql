@@ -0,0 +1,5 @@
+-- Add down migration script here
+
+-- delete wishlist share links and gift reservations
+DROP TABLE IF EXISTS gift_reservations;
+DROP TABLE IF EXISTS wishlist_share_links;
@@ -0,0 +1,27 @@
+-- Add up migration script here
+CREATE EXTENSION IF NOT EXISTS pgcrypto;
+
+-- wishlist_share_links table
+-- an unguessable token that opens a read-only view of a user's wishlist to anyone
+-- holding it, no account needed. a user has at most one link, rotating it changes
+-- the token and revoking it deletes the row
+CREATE TABLE
+    IF NOT EXISTS wishlist_share_links (
+        share_token TEXT PRIMARY KEY NOT NULL DEFAULT encode(gen_random_bytes(24), 'hex'),
+        user_id UUID NOT NULL UNIQUE REFERENCES users (user_id) ON DELETE CASCADE,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+    );
+
+-- gift_reservations table
+-- a visitor to a shared wishlist saying they'll buy a record. reserved records are
+-- hidden from other visitors and never shown to the wishlist's owner.
+-- cancel_token lets whoever reserved it, who has no account, take it back
+CREATE TABLE
+    IF NOT EXISTS gift_reservations (
+        reservation_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        user_wish_list_id UUID NOT NULL UNIQUE REFERENCES user_wishlist (user_wish_list_id) ON DELETE CASCADE,
+        reserver_name VARCHAR(100) NOT NULL,
+        note TEXT,
+        cancel_token TEXT NOT NULL UNIQUE DEFAULT encode(gen_random_bytes(24), 'hex'),
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+    );
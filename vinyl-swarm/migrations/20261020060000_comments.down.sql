@@ -0,0 +1,10 @@
+-- Add down migration script here
+
+-- delete comments and their reports
+DELETE FROM notifications WHERE notification_type = 'comment';
+DELETE FROM notification_preferences WHERE notification_type = 'comment';
+ALTER TABLE notifications
+    DROP CONSTRAINT IF EXISTS valid_notification_type,
+    ADD CONSTRAINT valid_notification_type CHECK (notification_type IN ('new_follower', 'wishlist_match', 'store_claim', 'direct_message'));
+DROP TABLE IF EXISTS comment_reports;
+DROP TABLE IF EXISTS comments;
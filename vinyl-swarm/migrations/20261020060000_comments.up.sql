@@ -0,0 +1,56 @@
+-- Add up migration script here
+
+-- comments table
+-- threaded comments on exactly one of a record, a record store or a user's collection.
+-- deleted_at is set when the author deletes a comment and removed_at when a moderator
+-- removes it, either way replies keep their place in the thread
+CREATE TABLE
+    IF NOT EXISTS comments (
+        comment_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        user_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        record_id UUID REFERENCES records (record_id) ON DELETE CASCADE,
+        record_store_id UUID REFERENCES record_stores (record_store_id) ON DELETE CASCADE,
+        collection_user_id UUID REFERENCES users (user_id) ON DELETE CASCADE,
+        parent_comment_id UUID REFERENCES comments (comment_id) ON DELETE CASCADE,
+        body TEXT NOT NULL,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        updated_at TIMESTAMP WITH TIME ZONE,
+        deleted_at TIMESTAMP WITH TIME ZONE,
+        removed_at TIMESTAMP WITH TIME ZONE,
+        removed_by UUID REFERENCES users (user_id) ON DELETE SET NULL,
+        removal_reason TEXT,
+        CONSTRAINT one_comment_target CHECK (num_nonnulls(record_id, record_store_id, collection_user_id) = 1),
+        CONSTRAINT comment_not_empty CHECK (length(trim(body)) > 0)
+    );
+
+CREATE INDEX IF NOT EXISTS comments_record_idx ON comments (record_id) WHERE record_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS comments_record_store_idx ON comments (record_store_id) WHERE record_store_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS comments_collection_idx ON comments (collection_user_id) WHERE collection_user_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS comments_parent_idx ON comments (parent_comment_id);
+
+-- comment_reports table
+-- users flagging a comment for the admin queue. a report is pending until an admin
+-- dismisses it or removes the comment, which settles every pending report on it.
+-- a user can only have one pending report on a comment
+CREATE TABLE
+    IF NOT EXISTS comment_reports (
+        report_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        comment_id UUID NOT NULL REFERENCES comments (comment_id) ON DELETE CASCADE,
+        reporter_id UUID NOT NULL REFERENCES users (user_id) ON DELETE CASCADE,
+        reason VARCHAR(20) NOT NULL,
+        details TEXT,
+        report_status VARCHAR(20) NOT NULL DEFAULT 'pending',
+        reviewer_id UUID REFERENCES users (user_id) ON DELETE SET NULL,
+        reviewed_at TIMESTAMP WITH TIME ZONE,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        CONSTRAINT valid_report_reason CHECK (reason IN ('spam', 'harassment', 'hate', 'off_topic', 'other')),
+        CONSTRAINT valid_report_status CHECK (report_status IN ('pending', 'dismissed', 'actioned'))
+    );
+
+CREATE UNIQUE INDEX IF NOT EXISTS unique_pending_report
+    ON comment_reports (comment_id, reporter_id) WHERE report_status = 'pending';
+
+-- replies and comments on a user's collection are a type of notification
+ALTER TABLE notifications
+    DROP CONSTRAINT IF EXISTS valid_notification_type,
+    ADD CONSTRAINT valid_notification_type CHECK (notification_type IN ('new_follower', 'wishlist_match', 'store_claim', 'direct_message', 'comment'));
@@ -0,0 +1,9 @@
+-- Add down migration script here
+
+-- delete the search indexes and the location profile field
+DROP INDEX IF EXISTS users_city_idx;
+DROP INDEX IF EXISTS users_last_name_search_idx;
+DROP INDEX IF EXISTS users_first_name_search_idx;
+DROP INDEX IF EXISTS users_user_name_search_idx;
+ALTER TABLE users
+    DROP COLUMN IF EXISTS user_city;
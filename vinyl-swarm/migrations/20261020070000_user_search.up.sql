@@ -0,0 +1,11 @@
+-- Add up migration script here
+
+-- an optional location on the public profile, used to find collectors nearby
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS user_city VARCHAR(100);
+
+-- prefix searches on user names, real names and cities ignore case
+CREATE INDEX IF NOT EXISTS users_user_name_search_idx ON users (lower(user_name) text_pattern_ops);
+CREATE INDEX IF NOT EXISTS users_first_name_search_idx ON users (lower(user_first_name) text_pattern_ops);
+CREATE INDEX IF NOT EXISTS users_last_name_search_idx ON users (lower(user_last_name) text_pattern_ops);
+CREATE INDEX IF NOT EXISTS users_city_idx ON users (lower(user_city));
@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+-- delete the listening log
+DROP TABLE IF EXISTS record_plays;
@@ -0,0 +1,16 @@
+-- Add up migration script here
+
+-- record_plays table
+-- a listening log on collection items, side is which side was spun
+-- or NULL for the whole record
+CREATE TABLE
+    IF NOT EXISTS record_plays (
+        play_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        user_record_id UUID NOT NULL REFERENCES user_records (user_record_id) ON DELETE CASCADE,
+        played_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+        side VARCHAR(10),
+        notes TEXT,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+    );
+
+CREATE INDEX IF NOT EXISTS record_plays_user_record_idx ON record_plays (user_record_id, played_at DESC);
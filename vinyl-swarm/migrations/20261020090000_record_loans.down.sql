@@ -0,0 +1,4 @@
+-- Add down migration script here
+
+-- delete the lending tracker
+DROP TABLE IF EXISTS record_loans;
@@ -0,0 +1,25 @@
+-- Add up migration script here
+
+-- record_loans table
+-- collection items lent out. borrower_id is set when the borrower has an account,
+-- borrower_name always holds who has the record so a deleted account doesn't lose it.
+-- returned_date stays NULL while the record is out
+CREATE TABLE
+    IF NOT EXISTS record_loans (
+        loan_id UUID PRIMARY KEY NOT NULL DEFAULT uuid_generate_v4(),
+        user_record_id UUID NOT NULL REFERENCES user_records (user_record_id) ON DELETE CASCADE,
+        borrower_id UUID REFERENCES users (user_id) ON DELETE SET NULL,
+        borrower_name VARCHAR(100) NOT NULL,
+        lent_date DATE NOT NULL DEFAULT CURRENT_DATE,
+        due_date DATE,
+        returned_date DATE,
+        notes TEXT,
+        created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW(),
+        CONSTRAINT due_after_lent CHECK (due_date >= lent_date),
+        CONSTRAINT returned_after_lent CHECK (returned_date >= lent_date)
+    );
+
+-- a record can only be with one borrower at a time
+CREATE UNIQUE INDEX IF NOT EXISTS record_loans_one_open_idx ON record_loans (user_record_id)
+    WHERE returned_date IS NULL;
+CREATE INDEX IF NOT EXISTS record_loans_borrower_idx ON record_loans (borrower_id);
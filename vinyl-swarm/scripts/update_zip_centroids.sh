@@ -0,0 +1,22 @@
+#!/bin/sh
+# rebuilds data/us_zip_centroids.csv from the Census Bureau's national ZCTA gazetteer,
+# one row per ZIP Code Tabulation Area with its internal point as the centroid.
+# usage: scripts/update_zip_centroids.sh [gazetteer year, defaults to 2023]
+set -eu
+
+year="${1:-2023}"
+url="https://www2.census.gov/geo/docs/maps-data/data/gazetteer/${year}_Gazetteer/${year}_Gaz_zcta_national.zip"
+out="$(dirname "$0")/../data/us_zip_centroids.csv"
+tmp="$(mktemp -d)"
+trap 'rm -rf "$tmp"' EXIT
+
+curl -fsSL -o "$tmp/gazetteer.zip" "$url"
+unzip -q -o "$tmp/gazetteer.zip" -d "$tmp"
+
+# the gazetteer is tab separated: GEOID ALAND AWATER ALAND_SQMI AWATER_SQMI INTPTLAT INTPTLONG
+{
+    echo "zip,latitude,longitude"
+    awk -F '\t' 'NR > 1 { gsub(/[ \r]/, "", $7); printf "%s,%s,%s\n", $1, $6, $7 }' "$tmp"/*.txt | sort
+} > "$out"
+
+echo "wrote $(($(wc -l < "$out") - 1)) zip centroids to $out"
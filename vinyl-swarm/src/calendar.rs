@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::event::CalendarEventModel;
+
+/// RFC 5545 caps content lines at 75 octets, longer ones are folded
+const MAX_LINE_OCTETS: usize = 75;
+
+/// ics_timestamp:
+/// a UTC date-time in the basic format calendars expect, e.g. 20260418T120000Z
+fn ics_timestamp(time: &DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// escape_text:
+/// backslashes, commas, semicolons and newlines have to be escaped in TEXT values
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// push_line:
+/// appends a CRLF terminated content line, folding it onto continuation lines
+/// that start with a space without splitting a multi-byte character
+fn push_line(ics: &mut String, line: &str) {
+    let mut octets = 0;
+    for c in line.chars() {
+        if octets + c.len_utf8() > MAX_LINE_OCTETS {
+            ics.push_str("\r\n ");
+            octets = 1;
+        }
+        ics.push(c);
+        octets += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+/// events_to_ics:
+/// an iCalendar feed with one VEVENT per store event. the description lists
+/// the records tied to the event and the location is the store's address
+pub fn events_to_ics(calendar_name: &str, events: &[CalendarEventModel]) -> String {
+    let mut ics = String::new();
+    let now = Utc::now();
+
+    push_line(&mut ics, "BEGIN:VCALENDAR");
+    push_line(&mut ics, "VERSION:2.0");
+    push_line(&mut ics, "PRODID:-//vinyl swarm//store events//EN");
+    push_line(&mut ics, "CALSCALE:GREGORIAN");
+    push_line(&mut ics, "METHOD:PUBLISH");
+    push_line(
+        &mut ics,
+        &format!("X-WR-CALNAME:{}", escape_text(calendar_name)),
+    );
+
+    for CalendarEventModel {
+        event,
+        record_store,
+    } in events
+    {
+        let mut description = event.description.clone().unwrap_or_default();
+        if !event.records.is_empty() {
+            if !description.is_empty() {
+                description.push_str("\n\n");
+            }
+            description.push_str("Records:");
+            for record in &event.records {
+                description.push_str(&format!("\n{} - {}", record.artist, record.title));
+            }
+        }
+
+        push_line(&mut ics, "BEGIN:VEVENT");
+        push_line(&mut ics, &format!("UID:{}@vinyl-swarm", event.event_id));
+        push_line(
+            &mut ics,
+            &format!(
+                "DTSTAMP:{}",
+                ics_timestamp(event.updated_at.as_ref().unwrap_or(&now))
+            ),
+        );
+        push_line(
+            &mut ics,
+            &format!("DTSTART:{}", ics_timestamp(&event.starts_at)),
+        );
+        if let Some(ends_at) = &event.ends_at {
+            push_line(&mut ics, &format!("DTEND:{}", ics_timestamp(ends_at)));
+        }
+        push_line(&mut ics, &format!("SUMMARY:{}", escape_text(&event.title)));
+        if !description.is_empty() {
+            push_line(
+                &mut ics,
+                &format!("DESCRIPTION:{}", escape_text(&description)),
+            );
+        }
+        push_line(
+            &mut ics,
+            &format!(
+                "LOCATION:{}",
+                escape_text(&format!(
+                    "{}, {}, {}, {} {}",
+                    record_store.store_name,
+                    record_store.store_address,
+                    record_store.store_city,
+                    record_store.store_state,
+                    record_store.store_zip
+                ))
+            ),
+        );
+        if let (Some(latitude), Some(longitude)) = (record_store.latitude, record_store.longitude) {
+            push_line(&mut ics, &format!("GEO:{:.6};{:.6}", latitude, longitude));
+        }
+        push_line(
+            &mut ics,
+            &format!("CATEGORIES:{}", escape_text(&event.event_type)),
+        );
+        push_line(&mut ics, "END:VEVENT");
+    }
+
+    push_line(&mut ics, "END:VCALENDAR");
+
+    ics
+}
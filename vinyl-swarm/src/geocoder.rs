@@ -0,0 +1,95 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+/// bundled `zip,latitude,longitude` centroids used to place record stores on the map
+/// without calling out to a geocoding service. built from the Census ZCTA gazetteer
+/// by scripts/update_zip_centroids.sh
+const ZIP_CENTROIDS_CSV: &str = include_str!("../data/us_zip_centroids.csv");
+
+static ZIP_CENTROIDS: OnceLock<HashMap<&'static str, (f64, f64)>> = OnceLock::new();
+
+/// zip_centroids:
+/// parses the bundled dataset the first time it's needed, skipping the header
+/// and any malformed rows
+fn zip_centroids() -> &'static HashMap<&'static str, (f64, f64)> {
+    ZIP_CENTROIDS.get_or_init(|| {
+        ZIP_CENTROIDS_CSV
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let mut columns = line.split(',').map(str::trim);
+                let zip = columns.next()?;
+                let latitude = columns.next()?.parse().ok()?;
+                let longitude = columns.next()?.parse().ok()?;
+                Some((zip, (latitude, longitude)))
+            })
+            .collect()
+    })
+}
+
+/// geocode_zip:
+/// resolves a US zip code, including zip+4 codes like "40206-1234",
+/// to the (latitude, longitude) of its centroid
+pub fn geocode_zip(zip: &str) -> Option<(f64, f64)> {
+    let zip5 = zip.trim().get(..5)?;
+    zip_centroids().get(zip5).copied()
+}
+
+/// the earth's mean radius, matching the haversine used to find nearby stores
+const EARTH_RADIUS_MILES: f64 = 3958.8;
+
+/// great_circle_miles:
+/// straight line distance in miles between two (latitude, longitude) points
+pub fn great_circle_miles(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lon1) = (from.0.to_radians(), from.1.to_radians());
+    let (lat2, lon2) = (to.0.to_radians(), to.1.to_radians());
+
+    let a = ((lat2 - lat1) / 2.0).sin().powi(2)
+        + lat1.cos() * lat2.cos() * ((lon2 - lon1) / 2.0).sin().powi(2);
+
+    EARTH_RADIUS_MILES * 2.0 * a.sqrt().asin()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(zip: &str, expected: (f64, f64)) {
+        let (latitude, longitude) =
+            geocode_zip(zip).unwrap_or_else(|| panic!("zip {} didn't resolve", zip));
+        assert!(
+            (latitude - expected.0).abs() < 0.1 && (longitude - expected.1).abs() < 0.1,
+            "zip {} resolved to ({}, {}), expected about {:?}",
+            zip,
+            latitude,
+            longitude,
+            expected
+        );
+    }
+
+    #[test]
+    fn resolves_zips_across_states() {
+        assert_near("02115", (42.34, -71.09)); // Boston, MA
+        assert_near("10003", (40.73, -73.99)); // New York, NY
+        assert_near("19106", (39.95, -75.15)); // Philadelphia, PA
+        assert_near("30307", (33.77, -84.34)); // Atlanta, GA
+        assert_near("60647", (41.92, -87.70)); // Chicago, IL
+        assert_near("70116", (29.97, -90.06)); // New Orleans, LA
+        assert_near("78704", (30.24, -97.77)); // Austin, TX
+        assert_near("80205", (39.76, -104.97)); // Denver, CO
+        assert_near("94110", (37.75, -122.42)); // San Francisco, CA
+        assert_near("98103", (47.67, -122.34)); // Seattle, WA
+    }
+
+    #[test]
+    fn resolves_zip_plus_four() {
+        assert_eq!(geocode_zip("40206-1234"), geocode_zip("40206"));
+        assert!(geocode_zip(" 40206 ").is_some());
+    }
+
+    #[test]
+    fn unknown_and_malformed_zips_dont_resolve() {
+        assert_eq!(geocode_zip("00000"), None);
+        assert_eq!(geocode_zip("123"), None);
+        assert_eq!(geocode_zip(""), None);
+    }
+}
@@ -0,0 +1,348 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    handlers::{internal_error, messages::is_blocked, notifications::notify, users::find_user},
+    models::activity::{
+        ActivityModel, ActivityOptions, FeedOptions, FilterOptions, FollowModel, FollowUserSchema,
+    },
+    AppState,
+};
+
+/// activities along with the record or store they were about, as read into ActivityModel.
+/// callers add their own joins and WHERE clause
+const ACTIVITY_QUERY: &str = "SELECT a.activity_id, a.user_id, u.user_name, a.activity_type,
+        a.review_id, a.created_at,
+        CASE WHEN a.record_id IS NULL THEN 'null'::jsonb ELSE to_jsonb(r) END AS record,
+        CASE WHEN a.record_store_id IS NULL THEN 'null'::jsonb ELSE to_jsonb(s) END AS record_store
+    FROM activity_events a
+    JOIN users u ON u.user_id = a.user_id
+    LEFT JOIN records r ON r.record_id = a.record_id
+    LEFT JOIN record_stores s ON s.record_store_id = a.record_store_id";
+
+/// record_activity:
+/// logs something a user did for their followers' feeds. the same record or store
+/// added again within a day isn't logged twice, and a failure to log never fails
+/// the change it describes
+pub async fn record_activity(
+    db: &Pool<Postgres>,
+    user_id: Uuid,
+    activity_type: &str,
+    record_id: Option<Uuid>,
+    record_store_id: Option<Uuid>,
+    review_id: Option<Uuid>,
+) {
+    let insert_result = sqlx::query!(
+        "INSERT INTO activity_events (user_id, activity_type, record_id, record_store_id, review_id)
+        SELECT $1, $2::VARCHAR, $3::uuid, $4::uuid, $5::uuid
+        WHERE NOT EXISTS (
+            SELECT 1 FROM activity_events
+            WHERE user_id = $1 AND activity_type = $2
+            AND record_id IS NOT DISTINCT FROM $3 AND record_store_id IS NOT DISTINCT FROM $4
+            AND created_at > NOW() - INTERVAL '1 day'
+        )",
+        user_id,
+        activity_type,
+        record_id,
+        record_store_id,
+        review_id
+    )
+    .execute(db)
+    .await;
+
+    if let Err(e) = insert_result {
+        println!(
+            "ACTIVITY: couldn't log {} for user {}: {:?}",
+            activity_type, user_id, e
+        );
+    }
+}
+
+/// POST follow another user
+pub async fn follow_user(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<FollowUserSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if user_id == body.followed_id {
+        let error_response = json!({
+            "status": "fail",
+            "message": "users can't follow themselves"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let follower = find_user(&data.db, user_id).await?;
+    let followed = find_user(&data.db, body.followed_id).await?;
+
+    if is_blocked(&data.db, user_id, body.followed_id)
+        .await
+        .map_err(internal_error)?
+    {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("{} can't follow {}", follower.user_name, followed.user_name)
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    let insert_result = sqlx::query!(
+        "INSERT INTO user_follows (follower_id, followed_id) VALUES ($1, $2)",
+        user_id,
+        body.followed_id
+    )
+    .execute(&data.db)
+    .await;
+
+    match insert_result {
+        Ok(_) => {
+            println!(
+                "POST: {} is following {}",
+                follower.user_name, followed.user_name
+            );
+
+            notify(
+                &data.db,
+                body.followed_id,
+                "new_follower",
+                &format!("{} started following you", follower.user_name),
+                Some(user_id),
+                Some(user_id),
+            )
+            .await;
+
+            Ok((
+                StatusCode::CREATED,
+                Json(json!({
+                    "status": "success",
+                    "user_id": user_id,
+                    "followed_id": body.followed_id,
+                })),
+            ))
+        }
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("{} already follows {}", follower.user_name, followed.user_name)
+            });
+            Err((StatusCode::CONFLICT, Json(error_response)))
+        }
+        Err(e) => Err(internal_error(e)),
+    }
+}
+
+/// DELETE stop following a user
+pub async fn unfollow_user(
+    Path((user_id, followed_id)): Path<(Uuid, Uuid)>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let delete_query = sqlx::query!(
+        "DELETE FROM user_follows WHERE follower_id = $1 AND followed_id = $2",
+        user_id,
+        followed_id
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?
+    .rows_affected();
+
+    if delete_query == 0 {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("user {} doesn't follow user {}", user_id, followed_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    }
+
+    println!("DELETE: user {} unfollowed user {}", user_id, followed_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET the users someone follows, most recently followed first
+pub async fn get_following(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_user(&data.db, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let following = sqlx::query_as!(
+        FollowModel,
+        "SELECT u.user_id, u.user_name, f.created_at AS followed_at
+        FROM user_follows f JOIN users u ON u.user_id = f.followed_id
+        WHERE f.follower_id = $1
+        ORDER BY f.created_at DESC LIMIT $2 OFFSET $3",
+        user_id,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!("GET: user {} follows {} users", user_id, following.len());
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": following.len(),
+        "following": following,
+    })))
+}
+
+/// GET a user's followers, most recent first
+pub async fn get_followers(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_user(&data.db, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let followers = sqlx::query_as!(
+        FollowModel,
+        "SELECT u.user_id, u.user_name, f.created_at AS followed_at
+        FROM user_follows f JOIN users u ON u.user_id = f.follower_id
+        WHERE f.followed_id = $1
+        ORDER BY f.created_at DESC LIMIT $2 OFFSET $3",
+        user_id,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} followers of user {}",
+        followers.len(),
+        user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": followers.len(),
+        "followers": followers,
+    })))
+}
+
+/// GET the recent activity of the people a user follows, newest first.
+/// users who keep their activity private are left out
+pub async fn get_feed(
+    Query(opts): Query<FeedOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_user(&data.db, opts.user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let feed = sqlx::query_as::<_, ActivityModel>(&format!(
+        "{}
+        JOIN user_follows f ON f.followed_id = a.user_id
+        WHERE f.follower_id = $1 AND u.activity_visibility <> 'private'
+        ORDER BY a.created_at DESC LIMIT $2 OFFSET $3",
+        ACTIVITY_QUERY
+    ))
+    .bind(opts.user_id)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} feed items for user {}",
+        feed.len(),
+        opts.user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": feed.len(),
+        "feed": feed,
+    })))
+}
+
+/// GET a user's own activity, newest first. public activity is open to anyone,
+/// followers only activity to their followers and private activity to the user alone.
+/// users who blocked the viewer, or were blocked by them, are never visible
+pub async fn get_user_activity(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<ActivityOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = find_user(&data.db, user_id).await?;
+
+    let blocked = match opts.viewer_id {
+        Some(viewer_id) if viewer_id != user_id => is_blocked(&data.db, viewer_id, user_id)
+            .await
+            .map_err(internal_error)?,
+        _ => false,
+    };
+
+    let visible = match (user.activity_visibility.as_str(), opts.viewer_id) {
+        _ if blocked => false,
+        (_, Some(viewer_id)) if viewer_id == user_id => true,
+        ("public", _) => true,
+        ("followers", Some(viewer_id)) => sqlx::query_scalar!(
+            r#"SELECT EXISTS (
+                SELECT 1 FROM user_follows WHERE follower_id = $1 AND followed_id = $2
+            ) AS "follows!""#,
+            viewer_id,
+            user_id
+        )
+        .fetch_one(&data.db)
+        .await
+        .map_err(internal_error)?,
+        _ => false,
+    };
+
+    if !visible {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("{}'s activity isn't visible to you", user.user_name)
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let activity = sqlx::query_as::<_, ActivityModel>(&format!(
+        "{} WHERE a.user_id = $1
+        ORDER BY a.created_at DESC LIMIT $2 OFFSET $3",
+        ACTIVITY_QUERY
+    ))
+    .bind(user_id)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} activity items for user {}",
+        activity.len(),
+        user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": activity.len(),
+        "activity": activity,
+    })))
+}
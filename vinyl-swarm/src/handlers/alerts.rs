@@ -0,0 +1,158 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::{
+    handlers::{internal_error, users::find_user},
+    models::alert::{AlertFilterOptions, MarkAlertsReadSchema, WishlistAlertModel},
+    AppState,
+};
+
+/// match_wishlist_alerts:
+/// alerts users whose wishlist has a record in stock at or under their target price.
+/// users who only want alerts from their favorite stores aren't told about the
+/// rest, and nobody is alerted about a copy they reported themselves.
+/// every alert sent or renewed also lands in the user's notifications unless
+/// they've turned wishlist matches off.
+/// listing_id limits the matching to one listing after an inventory change,
+/// user_id to one user's wishlist after it changes
+pub async fn match_wishlist_alerts<'e>(
+    executor: impl PgExecutor<'e>,
+    listing_id: Option<Uuid>,
+    user_id: Option<Uuid>,
+) -> Result<i64, sqlx::Error> {
+    let alerts_sent = sqlx::query_scalar!(
+        r#"WITH sent AS (
+            INSERT INTO wishlist_alerts (user_id, listing_id, price)
+            SELECT w.user_id, i.listing_id, i.price
+            FROM store_inventory i
+            JOIN user_wishlist w ON w.record_id = i.record_id
+            JOIN users u ON u.user_id = w.user_id
+            WHERE i.quantity > 0
+            AND (w.target_price IS NULL OR i.price <= w.target_price)
+            AND w.user_id IS DISTINCT FROM i.reported_by
+            AND (NOT u.alert_favorite_stores_only OR EXISTS (
+                SELECT 1 FROM user_record_stores f
+                WHERE f.user_key = w.user_id AND f.record_store_id = i.record_store_id
+            ))
+            AND ($1::uuid IS NULL OR i.listing_id = $1)
+            AND ($2::uuid IS NULL OR w.user_id = $2)
+            ON CONFLICT (user_id, listing_id) DO UPDATE
+            SET price = EXCLUDED.price, created_at = NOW(), read_at = NULL
+            WHERE EXCLUDED.price < wishlist_alerts.price
+            RETURNING alert_id, user_id, listing_id, price
+        ),
+        notified AS (
+            INSERT INTO notifications (user_id, notification_type, message, subject_id)
+            SELECT sent.user_id, 'wishlist_match',
+                r.artist || ' - ' || r.title || ' is in stock at ' || s.store_name
+                    || ' for $' || sent.price,
+                sent.alert_id
+            FROM sent
+            JOIN store_inventory i ON i.listing_id = sent.listing_id
+            JOIN records r ON r.record_id = i.record_id
+            JOIN record_stores s ON s.record_store_id = i.record_store_id
+            WHERE NOT EXISTS (
+                SELECT 1 FROM notification_preferences p
+                WHERE p.user_id = sent.user_id
+                AND p.notification_type = 'wishlist_match' AND NOT p.enabled
+            )
+        )
+        SELECT COUNT(*) AS "alerts_sent!" FROM sent"#,
+        listing_id,
+        user_id
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(alerts_sent)
+}
+
+/// GET a user's wishlist alerts, newest first
+pub async fn get_user_alerts(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<AlertFilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_user(&data.db, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let alerts = sqlx::query_as::<_, WishlistAlertModel>(
+        "SELECT a.alert_id, a.listing_id, a.price, i.condition, i.quantity, a.created_at, a.read_at,
+            to_jsonb(r) AS record, to_jsonb(s) AS record_store
+        FROM wishlist_alerts a
+        JOIN store_inventory i ON i.listing_id = a.listing_id
+        JOIN records r ON r.record_id = i.record_id
+        JOIN record_stores s ON s.record_store_id = i.record_store_id
+        WHERE a.user_id = $1 AND ($2 IS NOT TRUE OR a.read_at IS NULL)
+        ORDER BY a.created_at DESC LIMIT $3 OFFSET $4",
+    )
+    .bind(user_id)
+    .bind(opts.unread_only)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let unread = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "unread!" FROM wishlist_alerts WHERE user_id = $1 AND read_at IS NULL"#,
+        user_id
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} wishlist alerts for user_id: {}",
+        alerts.len(),
+        user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": alerts.len(),
+        "unread": unread,
+        "alerts": alerts,
+    })))
+}
+
+/// POST mark some or all of a user's wishlist alerts as read
+pub async fn mark_alerts_read(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<MarkAlertsReadSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_user(&data.db, user_id).await?;
+
+    let marked_read = sqlx::query!(
+        "UPDATE wishlist_alerts SET read_at = NOW()
+        WHERE user_id = $1 AND read_at IS NULL AND ($2::uuid[] IS NULL OR alert_id = ANY($2))",
+        user_id,
+        body.alert_ids.as_deref()
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?
+    .rows_affected();
+
+    println!(
+        "POST: marked {} wishlist alerts read for user_id: {}",
+        marked_read, user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "marked_read": marked_read,
+    })))
+}
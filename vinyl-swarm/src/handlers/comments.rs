@@ -0,0 +1,737 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use sqlx::{PgConnection, PgExecutor, Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    handlers::{
+        internal_error, messages::is_blocked, moderation::find_moderator, notifications::notify,
+        record_stores::check_store_exists, records::check_record_exists, store_claims::find_admin,
+        users::find_user,
+    },
+    models::comment::{
+        CommentModel, CommentReportModel, CreateCommentSchema, DeleteCommentOptions, FilterOptions,
+        RemoveCommentSchema, ReportCommentSchema, ReportFilterOptions, ResolveReportSchema,
+        StoredCommentModel, UpdateCommentSchema, COMMENT_MAX_CHARS, REPORT_REASONS,
+    },
+    AppState,
+};
+
+/// comments as read into CommentModel, hiding the body of deleted and removed ones.
+/// callers add their own WHERE clause
+const COMMENT_QUERY: &str = "SELECT c.comment_id, c.parent_comment_id, c.user_id, u.user_name,
+        CASE WHEN c.deleted_at IS NULL AND c.removed_at IS NULL THEN c.body END AS body,
+        CASE
+            WHEN c.removed_at IS NOT NULL THEN 'removed'
+            WHEN c.deleted_at IS NOT NULL THEN 'deleted'
+            ELSE 'visible'
+        END AS comment_status,
+        c.created_at, c.updated_at
+    FROM comments c JOIN users u ON u.user_id = c.user_id";
+
+/// what a comment is attached to
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CommentTarget {
+    Record(Uuid),
+    RecordStore(Uuid),
+    Collection(Uuid),
+}
+
+impl CommentTarget {
+    /// the record_id, record_store_id and collection_user_id columns for the target
+    fn columns(&self) -> (Option<Uuid>, Option<Uuid>, Option<Uuid>) {
+        match *self {
+            CommentTarget::Record(record_id) => (Some(record_id), None, None),
+            CommentTarget::RecordStore(record_store_id) => (None, Some(record_store_id), None),
+            CommentTarget::Collection(user_id) => (None, None, Some(user_id)),
+        }
+    }
+
+    /// what a stored comment is attached to
+    fn of(comment: &StoredCommentModel) -> CommentTarget {
+        match (
+            comment.record_id,
+            comment.record_store_id,
+            comment.collection_user_id,
+        ) {
+            (Some(record_id), _, _) => CommentTarget::Record(record_id),
+            (_, Some(record_store_id), _) => CommentTarget::RecordStore(record_store_id),
+            (_, _, Some(user_id)) => CommentTarget::Collection(user_id),
+            (None, None, None) => unreachable!("one_comment_target guarantees a target"),
+        }
+    }
+
+    /// NOT_FOUND when the record, store or user doesn't exist
+    async fn check_exists(
+        &self,
+        db: &Pool<Postgres>,
+    ) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+        match *self {
+            CommentTarget::Record(record_id) => check_record_exists(db, record_id).await,
+            CommentTarget::RecordStore(record_store_id) => {
+                check_store_exists(db, record_store_id).await
+            }
+            CommentTarget::Collection(user_id) => find_user(db, user_id).await.map(|_| ()),
+        }
+    }
+}
+
+/// check_comment_body:
+/// BAD_REQUEST for an empty comment or one over COMMENT_MAX_CHARS
+fn check_comment_body(body: &str) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let length = body.trim().chars().count();
+
+    if length == 0 || length > COMMENT_MAX_CHARS {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("a comment has to be between 1 and {} characters", COMMENT_MAX_CHARS)
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    Ok(())
+}
+
+/// find_stored_comment:
+/// a comment as stored, NOT_FOUND if there's no such comment
+async fn find_stored_comment<'e>(
+    executor: impl PgExecutor<'e>,
+    comment_id: Uuid,
+) -> Result<StoredCommentModel, (StatusCode, Json<serde_json::Value>)> {
+    let comment = sqlx::query_as!(
+        StoredCommentModel,
+        "SELECT comment_id, user_id, record_id, record_store_id, collection_user_id,
+            parent_comment_id, deleted_at, removed_at
+        FROM comments WHERE comment_id = $1",
+        comment_id
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(internal_error)?;
+
+    comment.ok_or_else(|| {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("comment_id {} not found", comment_id)
+        });
+        (StatusCode::NOT_FOUND, Json(error_response))
+    })
+}
+
+/// check_comment_open:
+/// CONFLICT when a comment was deleted by its author or removed by a moderator
+fn check_comment_open(
+    comment: &StoredCommentModel,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let closed = if comment.removed_at.is_some() {
+        "removed"
+    } else if comment.deleted_at.is_some() {
+        "deleted"
+    } else {
+        return Ok(());
+    };
+
+    let error_response = json!({
+        "status": "fail",
+        "message": format!("comment_id {} was {}", comment.comment_id, closed)
+    });
+    Err((StatusCode::CONFLICT, Json(error_response)))
+}
+
+/// fetch_comment:
+/// one comment as shown in a thread, without its replies
+async fn fetch_comment<'e>(
+    executor: impl PgExecutor<'e>,
+    comment_id: Uuid,
+) -> Result<CommentModel, sqlx::Error> {
+    sqlx::query_as::<_, CommentModel>(&format!("{} WHERE c.comment_id = $1", COMMENT_QUERY))
+        .bind(comment_id)
+        .fetch_one(executor)
+        .await
+}
+
+/// attach_replies:
+/// moves a comment's replies, and theirs, out of the map and into the comment
+fn attach_replies(comment: &mut CommentModel, replies: &mut HashMap<Uuid, Vec<CommentModel>>) {
+    if let Some(mut comment_replies) = replies.remove(&comment.comment_id) {
+        for reply in &mut comment_replies {
+            attach_replies(reply, replies);
+        }
+        comment.replies = comment_replies;
+    }
+}
+
+/// get_comments:
+/// a page of top level comments on a target, newest first, each with its
+/// whole thread of replies oldest first
+async fn get_comments(
+    db: &Pool<Postgres>,
+    target: CommentTarget,
+    opts: FilterOptions,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    target.check_exists(db).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+    let (record_id, record_store_id, collection_user_id) = target.columns();
+
+    let comments = sqlx::query_as::<_, CommentModel>(&format!(
+        "WITH RECURSIVE top_level AS (
+            SELECT comment_id FROM comments
+            WHERE parent_comment_id IS NULL
+            AND record_id IS NOT DISTINCT FROM $1
+            AND record_store_id IS NOT DISTINCT FROM $2
+            AND collection_user_id IS NOT DISTINCT FROM $3
+            ORDER BY created_at DESC LIMIT $4 OFFSET $5
+        ),
+        thread AS (
+            SELECT comment_id FROM top_level
+            UNION ALL
+            SELECT reply.comment_id FROM comments reply
+            JOIN thread ON reply.parent_comment_id = thread.comment_id
+        )
+        {} WHERE c.comment_id IN (SELECT comment_id FROM thread)
+        ORDER BY c.created_at",
+        COMMENT_QUERY
+    ))
+    .bind(record_id)
+    .bind(record_store_id)
+    .bind(collection_user_id)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(db)
+    .await
+    .map_err(internal_error)?;
+
+    let total = comments.len();
+    let (mut top_level, replies): (Vec<_>, Vec<_>) = comments
+        .into_iter()
+        .partition(|comment| comment.parent_comment_id.is_none());
+
+    let mut replies_by_parent: HashMap<Uuid, Vec<CommentModel>> = HashMap::new();
+    for reply in replies {
+        if let Some(parent_comment_id) = reply.parent_comment_id {
+            replies_by_parent
+                .entry(parent_comment_id)
+                .or_default()
+                .push(reply);
+        }
+    }
+
+    top_level.reverse();
+    for comment in &mut top_level {
+        attach_replies(comment, &mut replies_by_parent);
+    }
+
+    println!(
+        "GET: returning {} comment threads ({} comments) on {:?}",
+        top_level.len(),
+        total,
+        target
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": top_level.len(),
+        "comments": top_level,
+    })))
+}
+
+/// create_comment:
+/// adds a comment or reply to a target. the author of the comment being replied to,
+/// or the owner of the collection being commented on, gets a notification, and
+/// nobody can reply to or comment on the collection of a user they're blocked from
+async fn create_comment(
+    db: &Pool<Postgres>,
+    target: CommentTarget,
+    body: CreateCommentSchema,
+) -> Result<(StatusCode, Json<serde_json::Value>), (StatusCode, Json<serde_json::Value>)> {
+    check_comment_body(&body.body)?;
+
+    let author = find_user(db, body.user_id).await?;
+    target.check_exists(db).await?;
+
+    // whoever is told about the comment
+    let mut addressee = match target {
+        CommentTarget::Collection(user_id) if body.parent_comment_id.is_none() => Some((
+            user_id,
+            format!("{} commented on your collection", author.user_name),
+        )),
+        _ => None,
+    };
+
+    if let Some(parent_comment_id) = body.parent_comment_id {
+        let parent = find_stored_comment(db, parent_comment_id).await?;
+
+        if CommentTarget::of(&parent) != target {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("comment_id {} is on something else", parent_comment_id)
+            });
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+
+        check_comment_open(&parent)?;
+
+        addressee = Some((
+            parent.user_id,
+            format!("{} replied to your comment", author.user_name),
+        ));
+    }
+
+    if let Some((addressee_id, _)) = &addressee {
+        if *addressee_id != author.user_id
+            && is_blocked(db, author.user_id, *addressee_id)
+                .await
+                .map_err(internal_error)?
+        {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("{} can't comment here", author.user_name)
+            });
+            return Err((StatusCode::FORBIDDEN, Json(error_response)));
+        }
+    }
+
+    let (record_id, record_store_id, collection_user_id) = target.columns();
+
+    let comment_id = sqlx::query_scalar!(
+        "INSERT INTO comments (user_id, record_id, record_store_id, collection_user_id,
+            parent_comment_id, body)
+        VALUES ($1, $2, $3, $4, $5, $6) RETURNING comment_id",
+        body.user_id,
+        record_id,
+        record_store_id,
+        collection_user_id,
+        body.parent_comment_id,
+        body.body.trim()
+    )
+    .fetch_one(db)
+    .await
+    .map_err(internal_error)?;
+
+    let comment = fetch_comment(db, comment_id)
+        .await
+        .map_err(internal_error)?;
+
+    if let Some((addressee_id, message)) = addressee {
+        if addressee_id != author.user_id {
+            notify(
+                db,
+                addressee_id,
+                "comment",
+                &message,
+                Some(author.user_id),
+                Some(comment_id),
+            )
+            .await;
+        }
+    }
+
+    println!("POST: {} commented on {:?}", author.user_name, target);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "comment": comment,
+        })),
+    ))
+}
+
+/// GET the comments on a record
+pub async fn get_record_comments(
+    Path(record_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    get_comments(&data.db, CommentTarget::Record(record_id), opts).await
+}
+
+/// POST comment on a record
+pub async fn create_record_comment(
+    Path(record_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<CreateCommentSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    create_comment(&data.db, CommentTarget::Record(record_id), body).await
+}
+
+/// GET the comments on a record store
+pub async fn get_store_comments(
+    Path(record_store_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    get_comments(&data.db, CommentTarget::RecordStore(record_store_id), opts).await
+}
+
+/// POST comment on a record store
+pub async fn create_store_comment(
+    Path(record_store_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<CreateCommentSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    create_comment(&data.db, CommentTarget::RecordStore(record_store_id), body).await
+}
+
+/// GET the comments on a user's collection
+pub async fn get_collection_comments(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    get_comments(&data.db, CommentTarget::Collection(user_id), opts).await
+}
+
+/// POST comment on a user's collection
+pub async fn create_collection_comment(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<CreateCommentSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    create_comment(&data.db, CommentTarget::Collection(user_id), body).await
+}
+
+/// PATCH edit a comment, by its author
+pub async fn edit_comment(
+    Path(comment_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<UpdateCommentSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_comment_body(&body.body)?;
+
+    let stored = find_stored_comment(&data.db, comment_id).await?;
+
+    if stored.user_id != body.user_id {
+        let error_response = json!({
+            "status": "fail",
+            "message": "only the author can edit a comment"
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    check_comment_open(&stored)?;
+
+    sqlx::query!(
+        "UPDATE comments SET body = $1, updated_at = NOW() WHERE comment_id = $2",
+        body.body.trim(),
+        comment_id
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let comment = fetch_comment(&data.db, comment_id)
+        .await
+        .map_err(internal_error)?;
+
+    println!("PATCH: edited comment {}", comment_id);
+
+    Ok(Json(json!({
+        "status": "success",
+        "comment": comment,
+    })))
+}
+
+/// DELETE a comment, by its author. replies stay in the thread
+pub async fn delete_comment(
+    Path(comment_id): Path<Uuid>,
+    Query(opts): Query<DeleteCommentOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let stored = find_stored_comment(&data.db, comment_id).await?;
+
+    if stored.user_id != opts.user_id {
+        let error_response = json!({
+            "status": "fail",
+            "message": "only the author can delete a comment, moderators remove them"
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    sqlx::query!(
+        "UPDATE comments SET deleted_at = COALESCE(deleted_at, NOW()) WHERE comment_id = $1",
+        comment_id
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!("DELETE: comment {} deleted by its author", comment_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// take_down_comment:
+/// removes a comment on a moderator's or admin's say so and settles every
+/// pending report on it
+async fn take_down_comment(
+    conn: &mut PgConnection,
+    comment_id: Uuid,
+    remover_id: Uuid,
+    removal_reason: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "UPDATE comments SET removed_at = NOW(), removed_by = $1, removal_reason = $2
+        WHERE comment_id = $3",
+        remover_id,
+        removal_reason,
+        comment_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE comment_reports SET report_status = 'actioned', reviewer_id = $1, reviewed_at = NOW()
+        WHERE comment_id = $2 AND report_status = 'pending'",
+        remover_id,
+        comment_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// POST remove a comment as a moderator
+pub async fn remove_comment(
+    Path(comment_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<RemoveCommentSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let moderator = find_moderator(&data.db, body.moderator_id).await?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let stored = find_stored_comment(&mut *tx, comment_id).await?;
+
+    if stored.removed_at.is_some() {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("comment_id {} was already removed", comment_id)
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    take_down_comment(
+        &mut tx,
+        comment_id,
+        moderator.user_id,
+        body.removal_reason.as_deref(),
+    )
+    .await
+    .map_err(internal_error)?;
+
+    let comment = fetch_comment(&mut *tx, comment_id)
+        .await
+        .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    println!(
+        "POST: {} removed comment {}",
+        moderator.user_name, comment_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "comment": comment,
+    })))
+}
+
+/// POST report a comment to the admins
+pub async fn report_comment(
+    Path(comment_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<ReportCommentSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if !REPORT_REASONS.contains(&body.reason.as_str()) {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("reason must be one of {}", REPORT_REASONS.join(", "))
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let reporter = find_user(&data.db, body.user_id).await?;
+    let stored = find_stored_comment(&data.db, comment_id).await?;
+
+    if stored.user_id == body.user_id {
+        let error_response = json!({
+            "status": "fail",
+            "message": "users can't report their own comments"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    check_comment_open(&stored)?;
+
+    let insert_result = sqlx::query_scalar!(
+        "INSERT INTO comment_reports (comment_id, reporter_id, reason, details)
+        VALUES ($1, $2, $3, $4) RETURNING report_id",
+        comment_id,
+        body.user_id,
+        body.reason,
+        body.details
+    )
+    .fetch_one(&data.db)
+    .await;
+
+    match insert_result {
+        Ok(report_id) => {
+            println!(
+                "POST: {} reported comment {} for {}",
+                reporter.user_name, comment_id, body.reason
+            );
+
+            Ok((
+                StatusCode::CREATED,
+                Json(json!({
+                    "status": "success",
+                    "report_id": report_id,
+                    "comment_id": comment_id,
+                    "report_status": "pending",
+                })),
+            ))
+        }
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("{} already reported this comment", reporter.user_name)
+            });
+            Err((StatusCode::CONFLICT, Json(error_response)))
+        }
+        Err(e) => Err(internal_error(e)),
+    }
+}
+
+/// GET the comment report queue
+/// defaults to pending reports, oldest first
+pub async fn list_comment_reports(
+    Query(opts): Query<ReportFilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_admin(&data.db, opts.admin_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let reports = sqlx::query_as!(
+        CommentReportModel,
+        r#"SELECT r.report_id, r.comment_id, r.reporter_id, reporter.user_name AS reporter_name,
+            r.reason, r.details, r.report_status, r.reviewer_id, r.reviewed_at, r.created_at,
+            c.user_id AS comment_author_id, author.user_name AS comment_author_name,
+            c.body AS comment_body, c.removed_at AS comment_removed_at,
+            (SELECT COUNT(*) FROM comment_reports p
+                WHERE p.comment_id = r.comment_id AND p.report_status = 'pending') AS "pending_reports!"
+        FROM comment_reports r
+        JOIN comments c ON c.comment_id = r.comment_id
+        JOIN users reporter ON reporter.user_id = r.reporter_id
+        JOIN users author ON author.user_id = c.user_id
+        WHERE r.report_status = $1
+        ORDER BY r.created_at LIMIT $2 OFFSET $3"#,
+        opts.report_status.as_deref().unwrap_or("pending"),
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!("GET: returning {} comment reports", reports.len());
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": reports.len(),
+        "reports": reports,
+    })))
+}
+
+/// POST settle a pending report by dismissing it or removing the comment
+pub async fn resolve_comment_report(
+    Path(report_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<ResolveReportSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if !matches!(body.action.as_str(), "dismiss" | "remove") {
+        let error_response = json!({
+            "status": "fail",
+            "message": "action must be dismiss or remove"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let admin = find_admin(&data.db, body.admin_id).await?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let report = sqlx::query!(
+        "SELECT comment_id, report_status FROM comment_reports WHERE report_id = $1 FOR UPDATE",
+        report_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(report) = report else {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("report_id {} not found", report_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    if report.report_status != "pending" {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("report_id {} was already {}", report_id, report.report_status)
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    if body.action == "remove" {
+        take_down_comment(
+            &mut tx,
+            report.comment_id,
+            admin.user_id,
+            body.removal_reason.as_deref(),
+        )
+        .await
+        .map_err(internal_error)?;
+    } else {
+        sqlx::query!(
+            "UPDATE comment_reports SET report_status = 'dismissed', reviewer_id = $1, reviewed_at = NOW()
+            WHERE report_id = $2",
+            admin.user_id,
+            report_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+    }
+
+    tx.commit().await.map_err(internal_error)?;
+
+    let comment = fetch_comment(&data.db, report.comment_id)
+        .await
+        .map_err(internal_error)?;
+
+    println!(
+        "POST: {} resolved report {} with {}",
+        admin.user_name, report_id, body.action
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "report_id": report_id,
+        "action": body.action,
+        "comment": comment,
+    })))
+}
@@ -0,0 +1,487 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use sqlx::{PgConnection, PgExecutor, Pool, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::{
+    calendar::events_to_ics,
+    handlers::{
+        internal_error,
+        record_stores::check_store_exists,
+        store_claims::{check_store_manager, store_owner_ids},
+        users::find_user,
+    },
+    models::event::{
+        CalendarEventModel, CreateStoreEventSchema, DeleteEventOptions, FilterOptions,
+        StoreEventModel, UpdateStoreEventSchema, EVENT_TYPES,
+    },
+    models::user::UserModel,
+    AppState,
+};
+
+/// an event's columns along with the records tied to it, as read into StoreEventModel
+const EVENT_COLUMNS: &str = "e.*, COALESCE((
+        SELECT jsonb_agg(to_jsonb(r) ORDER BY r.artist, r.title)
+        FROM store_event_records er JOIN records r USING (record_id)
+        WHERE er.event_id = e.event_id), '[]'::jsonb) AS records";
+
+/// calendar feeds keep events that finished in the last 30 days,
+/// so calendar apps don't drop them the moment they're over
+const CALENDAR_HISTORY_DAYS: i32 = 30;
+
+/// check_event:
+/// events need a title, a known event_type and to end after they start
+fn check_event(
+    title: Option<&str>,
+    event_type: Option<&str>,
+    starts_at: &DateTime<Utc>,
+    ends_at: Option<&DateTime<Utc>>,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let message = if title.is_some_and(|title| title.trim().is_empty()) {
+        "title can't be empty".to_string()
+    } else if event_type.is_some_and(|event_type| !EVENT_TYPES.contains(&event_type)) {
+        format!("event_type must be one of {}", EVENT_TYPES.join(", "))
+    } else if ends_at.is_some_and(|ends_at| ends_at <= starts_at) {
+        "ends_at must be after starts_at".to_string()
+    } else {
+        return Ok(());
+    };
+
+    Err((
+        StatusCode::BAD_REQUEST,
+        Json(json!({"status": "fail", "message": message})),
+    ))
+}
+
+/// check_event_manager:
+/// events are changed by their creator, the store's verified owners or a moderator
+async fn check_event_manager(
+    db: &Pool<Postgres>,
+    event: &StoreEventModel,
+    user: &UserModel,
+    action: &str,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if event.created_by == Some(user.user_id) || user.is_moderator() {
+        return Ok(());
+    }
+
+    let owner_ids = store_owner_ids(db, event.record_store_id)
+        .await
+        .map_err(internal_error)?;
+
+    if !owner_ids.contains(&user.user_id) {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("only the event's creator, the store's owners or a moderator can {} an event", action)
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    Ok(())
+}
+
+/// check_event_records:
+/// 404 for the first record_id that isn't in the catalog
+async fn check_event_records(
+    db: &Pool<Postgres>,
+    record_ids: &[Uuid],
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let found: Vec<Uuid> = sqlx::query_scalar!(
+        "SELECT record_id FROM records WHERE record_id = ANY($1)",
+        record_ids
+    )
+    .fetch_all(db)
+    .await
+    .map_err(internal_error)?;
+
+    if let Some(missing) = record_ids.iter().find(|id| !found.contains(id)) {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("record_id {} not found", missing)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    }
+
+    Ok(())
+}
+
+/// replace_event_records:
+/// swaps the records tied to an event for the supplied ones
+async fn replace_event_records(
+    conn: &mut PgConnection,
+    event_id: Uuid,
+    record_ids: &[Uuid],
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM store_event_records WHERE event_id = $1",
+        event_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "INSERT INTO store_event_records (event_id, record_id)
+        SELECT $1, UNNEST($2::uuid[]) ON CONFLICT DO NOTHING",
+        event_id,
+        record_ids
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// fetch_store_event:
+/// an event at a store along with its records
+async fn fetch_store_event<'e>(
+    executor: impl PgExecutor<'e>,
+    record_store_id: Uuid,
+    event_id: Uuid,
+) -> Result<StoreEventModel, (StatusCode, Json<serde_json::Value>)> {
+    let event = sqlx::query_as::<_, StoreEventModel>(&format!(
+        "SELECT {} FROM store_events e WHERE e.event_id = $1 AND e.record_store_id = $2",
+        EVENT_COLUMNS
+    ))
+    .bind(event_id)
+    .bind(record_store_id)
+    .fetch_optional(executor)
+    .await
+    .map_err(internal_error)?;
+
+    event.ok_or_else(|| {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("event_id {} not found at record store {}", event_id, record_store_id)
+        });
+        (StatusCode::NOT_FOUND, Json(error_response))
+    })
+}
+
+/// fetch_favorite_store_events:
+/// events at the stores a user has favorited that haven't finished
+/// by `since`, soonest first along with the store putting them on
+async fn fetch_favorite_store_events(
+    db: &Pool<Postgres>,
+    user_id: Uuid,
+    since: DateTime<Utc>,
+    limit: Option<usize>,
+    offset: usize,
+) -> Result<Vec<CalendarEventModel>, sqlx::Error> {
+    sqlx::query_as::<_, CalendarEventModel>(&format!(
+        "SELECT {}, to_jsonb(s) AS record_store
+        FROM store_events e
+        JOIN record_stores s USING (record_store_id)
+        JOIN user_record_stores f ON f.record_store_id = e.record_store_id
+        WHERE f.user_key = $1 AND COALESCE(e.ends_at, e.starts_at) >= $2
+        ORDER BY e.starts_at, s.store_name LIMIT $3 OFFSET $4",
+        EVENT_COLUMNS
+    ))
+    .bind(user_id)
+    .bind(since)
+    .bind(limit.map(|limit| limit as i64))
+    .bind(offset as i64)
+    .fetch_all(db)
+    .await
+}
+
+/// ics_response:
+/// an iCalendar feed served so calendar apps can subscribe to it
+fn ics_response(calendar_name: &str, events: &[CalendarEventModel]) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        events_to_ics(calendar_name, events),
+    )
+}
+
+/// GET a store's upcoming events, soonest first
+pub async fn get_store_events(
+    Path(record_store_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_store_exists(&data.db, record_store_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let events = sqlx::query_as::<_, StoreEventModel>(&format!(
+        "SELECT {} FROM store_events e
+        WHERE e.record_store_id = $1 AND COALESCE(e.ends_at, e.starts_at) >= NOW()
+        ORDER BY e.starts_at LIMIT $2 OFFSET $3",
+        EVENT_COLUMNS
+    ))
+    .bind(record_store_id)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} events for record store {}",
+        events.len(),
+        record_store_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": events.len(),
+        "events": events,
+    })))
+}
+
+/// GET a store's events as an iCalendar (.ics) feed
+pub async fn get_store_events_ics(
+    Path(record_store_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_store_exists(&data.db, record_store_id).await?;
+
+    let events = sqlx::query_as::<_, CalendarEventModel>(&format!(
+        "SELECT {}, to_jsonb(s) AS record_store
+        FROM store_events e JOIN record_stores s USING (record_store_id)
+        WHERE e.record_store_id = $1
+        AND COALESCE(e.ends_at, e.starts_at) >= NOW() - make_interval(days => $2)
+        ORDER BY e.starts_at",
+        EVENT_COLUMNS
+    ))
+    .bind(record_store_id)
+    .bind(CALENDAR_HISTORY_DAYS)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let store_name = sqlx::query_scalar!(
+        "SELECT store_name FROM record_stores WHERE record_store_id = $1",
+        record_store_id
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: exporting {} events for record store {} as ics",
+        events.len(),
+        record_store_id
+    );
+
+    Ok(ics_response(&format!("{} events", store_name), &events))
+}
+
+/// POST a new event at a store,
+/// stores with verified owners only take events from their owners
+pub async fn create_store_event(
+    Path(record_store_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<CreateStoreEventSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_event(
+        Some(&body.title),
+        body.event_type.as_deref(),
+        &body.starts_at,
+        body.ends_at.as_ref(),
+    )?;
+    let creator = find_user(&data.db, body.created_by).await?;
+    check_store_exists(&data.db, record_store_id).await?;
+    check_store_manager(&data.db, record_store_id, &creator).await?;
+
+    let record_ids = body.record_ids.unwrap_or_default();
+    check_event_records(&data.db, &record_ids).await?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let event_id = sqlx::query_scalar!(
+        "INSERT INTO store_events (record_store_id, title, description, event_type, starts_at, ends_at, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING event_id",
+        record_store_id,
+        body.title.trim(),
+        body.description,
+        body.event_type.as_deref().unwrap_or("other"),
+        body.starts_at,
+        body.ends_at,
+        body.created_by
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    replace_event_records(&mut tx, event_id, &record_ids)
+        .await
+        .map_err(internal_error)?;
+
+    let event = fetch_store_event(&mut *tx, record_store_id, event_id).await?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    println!(
+        "POST: added event '{}' at record store {}",
+        event.title, record_store_id
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "event": event,
+        })),
+    ))
+}
+
+/// PATCH an event, by its creator, the store's owners or a moderator
+pub async fn edit_store_event(
+    Path((record_store_id, event_id)): Path<(Uuid, Uuid)>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<UpdateStoreEventSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = find_user(&data.db, body.user_id).await?;
+    let event = fetch_store_event(&data.db, record_store_id, event_id).await?;
+
+    check_event_manager(&data.db, &event, &user, "edit").await?;
+
+    // the new times are checked against whichever of the old ones are kept
+    check_event(
+        body.title.as_deref(),
+        body.event_type.as_deref(),
+        body.starts_at.as_ref().unwrap_or(&event.starts_at),
+        body.ends_at
+            .as_ref()
+            .map_or(event.ends_at.as_ref(), Option::as_ref),
+    )?;
+
+    if let Some(record_ids) = &body.record_ids {
+        check_event_records(&data.db, record_ids).await?;
+    }
+
+    let mut update_query = QueryBuilder::<Postgres>::new("UPDATE store_events SET ");
+    let mut columns = update_query.separated(", ");
+
+    columns.push("updated_at = NOW()");
+    if let Some(title) = &body.title {
+        columns.push("title = ").push_bind_unseparated(title.trim());
+    }
+    if let Some(description) = &body.description {
+        columns
+            .push("description = ")
+            .push_bind_unseparated(description);
+    }
+    if let Some(event_type) = &body.event_type {
+        columns
+            .push("event_type = ")
+            .push_bind_unseparated(event_type);
+    }
+    if let Some(starts_at) = body.starts_at {
+        columns
+            .push("starts_at = ")
+            .push_bind_unseparated(starts_at);
+    }
+    if let Some(ends_at) = body.ends_at {
+        columns.push("ends_at = ").push_bind_unseparated(ends_at);
+    }
+
+    update_query.push(" WHERE event_id = ").push_bind(event_id);
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    update_query
+        .build()
+        .execute(&mut *tx)
+        .await
+        .map_err(internal_error)?;
+
+    if let Some(record_ids) = &body.record_ids {
+        replace_event_records(&mut tx, event_id, record_ids)
+            .await
+            .map_err(internal_error)?;
+    }
+
+    let event = fetch_store_event(&mut *tx, record_store_id, event_id).await?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    println!("PATCH: updated event {}", event_id);
+
+    Ok(Json(json!({
+        "status": "success",
+        "event": event,
+    })))
+}
+
+/// DELETE an event, by its creator, the store's owners or a moderator
+pub async fn delete_store_event(
+    Path((record_store_id, event_id)): Path<(Uuid, Uuid)>,
+    Query(opts): Query<DeleteEventOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = find_user(&data.db, opts.user_id).await?;
+    let event = fetch_store_event(&data.db, record_store_id, event_id).await?;
+
+    check_event_manager(&data.db, &event, &user, "delete").await?;
+
+    sqlx::query!("DELETE FROM store_events WHERE event_id = $1", event_id)
+        .execute(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    println!("DELETE: removed event: {}", event_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET upcoming events at a user's favorite stores, soonest first
+pub async fn get_user_events(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_user(&data.db, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let events = fetch_favorite_store_events(&data.db, user_id, Utc::now(), Some(limit), offset)
+        .await
+        .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} events at user {}'s favorite stores",
+        events.len(),
+        user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": events.len(),
+        "events": events,
+    })))
+}
+
+/// GET the events at a user's favorite stores as an iCalendar (.ics) feed
+pub async fn get_user_events_ics(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = find_user(&data.db, user_id).await?;
+
+    let since = Utc::now() - chrono::Duration::days(CALENDAR_HISTORY_DAYS.into());
+    let events = fetch_favorite_store_events(&data.db, user_id, since, None, 0)
+        .await
+        .map_err(internal_error)?;
+
+    println!(
+        "GET: exporting {} events at user {}'s favorite stores as ics",
+        events.len(),
+        user_id
+    );
+
+    Ok(ics_response(
+        &format!("{}'s record store events", user.user_name),
+        &events,
+    ))
+}
@@ -0,0 +1,348 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    handlers::{internal_error, moderation::find_moderator},
+    models::{
+        genre::{
+            CreateGenreAliasSchema, CreateGenreSchema, FilterOptions, GenreModel, TopRatedOptions,
+        },
+        record::RecordModel,
+    },
+    AppState,
+};
+
+/// find_genre:
+/// looks a genre up by its name or any of its aliases
+pub async fn find_genre(
+    db: &Pool<Postgres>,
+    genre_name: &str,
+) -> Result<GenreModel, (StatusCode, Json<serde_json::Value>)> {
+    let genre_query = sqlx::query_as!(
+        GenreModel,
+        r#"SELECT g.genre_id, g.genre_name, g.parent_genre_id,
+            COALESCE(array_agg(a.alias ORDER BY a.alias) FILTER (WHERE a.alias IS NOT NULL), '{}') AS "aliases!"
+        FROM genres g
+        LEFT JOIN genre_aliases a ON a.genre_id = g.genre_id
+        WHERE g.genre_id = (SELECT genre_id FROM genre_aliases WHERE alias = normalize_catalog_text($1))
+        GROUP BY g.genre_id"#,
+        genre_name
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(internal_error)?;
+
+    genre_query.ok_or_else(|| {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("genre '{}' not found", genre_name)
+        });
+        (StatusCode::NOT_FOUND, Json(error_response))
+    })
+}
+
+/// genre_with_subgenres:
+/// the canonical names of a genre and every genre below it in the taxonomy
+pub async fn genre_with_subgenres(
+    db: &Pool<Postgres>,
+    genre_id: Uuid,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"WITH RECURSIVE subgenres AS (
+            SELECT genre_id, genre_name FROM genres WHERE genre_id = $1
+            UNION
+            SELECT g.genre_id, g.genre_name FROM genres g
+            JOIN subgenres s ON g.parent_genre_id = s.genre_id
+        )
+        SELECT genre_name AS "genre_name!" FROM subgenres"#,
+        genre_id
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// genre_tree:
+/// nests the flat genre list under their parents
+fn genre_tree(genres: &[GenreModel], parent_genre_id: Option<Uuid>) -> Vec<serde_json::Value> {
+    genres
+        .iter()
+        .filter(|genre| genre.parent_genre_id == parent_genre_id)
+        .map(|genre| {
+            json!({
+                "genre_id": genre.genre_id,
+                "genre_name": genre.genre_name,
+                "aliases": genre.aliases,
+                "subgenres": genre_tree(genres, Some(genre.genre_id)),
+            })
+        })
+        .collect()
+}
+
+/// GET the genre taxonomy as a tree of top level genres and their sub-genres
+pub async fn list_genres(
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let genres = sqlx::query_as!(
+        GenreModel,
+        r#"SELECT g.genre_id, g.genre_name, g.parent_genre_id,
+            COALESCE(array_agg(a.alias ORDER BY a.alias) FILTER (WHERE a.alias IS NOT NULL), '{}') AS "aliases!"
+        FROM genres g
+        LEFT JOIN genre_aliases a ON a.genre_id = g.genre_id
+        GROUP BY g.genre_id
+        ORDER BY g.genre_name"#
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!("GET: returning {} genres", genres.len());
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": genres.len(),
+        "genres": genre_tree(&genres, None),
+    })))
+}
+
+/// GET records in a genre
+/// querying a parent genre includes the records of all of its sub-genres
+pub async fn get_genre_records(
+    Path(genre_name): Path<String>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let genre = find_genre(&data.db, &genre_name).await?;
+
+    let genre_names = genre_with_subgenres(&data.db, genre.genre_id)
+        .await
+        .map_err(internal_error)?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let records = sqlx::query_as!(
+        RecordModel,
+        "SELECT * FROM records WHERE genre && $1 ORDER BY artist LIMIT $2 OFFSET $3",
+        &genre_names,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} records in genre {}",
+        records.len(),
+        genre.genre_name
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "genre": genre.genre_name,
+        "subgenres": genre_names,
+        "results": records.len(),
+        "records": records,
+    })))
+}
+
+/// GET the best reviewed records in a genre and its sub-genres,
+/// ties go to the record with more reviews
+pub async fn get_top_rated_in_genre(
+    Path(genre_name): Path<String>,
+    Query(opts): Query<TopRatedOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let genre = find_genre(&data.db, &genre_name).await?;
+
+    let genre_names = genre_with_subgenres(&data.db, genre.genre_id)
+        .await
+        .map_err(internal_error)?;
+
+    let min_reviews = opts.min_reviews.unwrap_or(1).max(1);
+    let limit = opts.limit.unwrap_or(10);
+
+    let records = sqlx::query_as!(
+        RecordModel,
+        "SELECT * FROM records WHERE genre && $1 AND review_count >= $2
+        ORDER BY average_rating DESC, review_count DESC, artist LIMIT $3",
+        &genre_names,
+        min_reviews,
+        limit as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} top rated records in genre {}",
+        records.len(),
+        genre.genre_name
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "genre": genre.genre_name,
+        "min_reviews": min_reviews,
+        "results": records.len(),
+        "records": records,
+    })))
+}
+
+/// POST add a genre to the taxonomy, optionally below an existing parent genre
+pub async fn create_genre(
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<CreateGenreSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_moderator(&data.db, body.moderator_id).await?;
+
+    // the name doubles as the genre's first alias, so it has to survive normalizing
+    let alias = sqlx::query_scalar!(
+        r#"SELECT normalize_catalog_text($1) AS "alias!""#,
+        body.genre_name
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    if alias.is_empty() {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("genre_name '{}' has no letters or numbers", body.genre_name)
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    // an existing spelling would make the new genre unreachable
+    if let Ok(existing) = find_genre(&data.db, &body.genre_name).await {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("genre '{}' already exists as '{}'", body.genre_name, existing.genre_name)
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    let parent_genre_id = match &body.parent_genre {
+        Some(parent_genre) => Some(find_genre(&data.db, parent_genre).await?.genre_id),
+        None => None,
+    };
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let genre_id = sqlx::query_scalar!(
+        "INSERT INTO genres (genre_name, parent_genre_id) VALUES ($1, $2) RETURNING genre_id",
+        body.genre_name.trim(),
+        parent_genre_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|e| genre_insert_error(&body.genre_name, e))?;
+
+    sqlx::query!(
+        "INSERT INTO genre_aliases (alias, genre_id) VALUES ($1, $2)",
+        alias,
+        genre_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| genre_insert_error(&body.genre_name, e))?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    let genre = find_genre(&data.db, &body.genre_name).await?;
+
+    println!("POST: created genre {}", genre.genre_name);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "genre": genre,
+        })),
+    ))
+}
+
+/// genre_insert_error:
+/// CONFLICT when a genre created at the same time took the name first
+fn genre_insert_error(genre_name: &str, e: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    match e {
+        sqlx::Error::Database(db_error) if db_error.is_unique_violation() => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("genre '{}' already exists", genre_name)
+            });
+            (StatusCode::CONFLICT, Json(error_response))
+        }
+        e => internal_error(e),
+    }
+}
+
+/// POST map another spelling onto an existing genre
+pub async fn add_genre_alias(
+    Path(genre_name): Path<String>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<CreateGenreAliasSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_moderator(&data.db, body.moderator_id).await?;
+
+    let genre = find_genre(&data.db, &genre_name).await?;
+
+    // aliases are stored the way lookups normalize them, punctuation alone
+    // normalizes to nothing and could never be looked up
+    let alias = sqlx::query_scalar!(
+        r#"SELECT normalize_catalog_text($1) AS "alias!""#,
+        body.alias
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    if alias.is_empty() {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("alias '{}' has no letters or numbers", body.alias)
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let alias_insert = sqlx::query!(
+        "INSERT INTO genre_aliases (alias, genre_id) VALUES ($1, $2)",
+        alias,
+        genre.genre_id
+    )
+    .execute(&data.db)
+    .await;
+
+    match alias_insert {
+        Ok(_) => {}
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("alias '{}' is already in use", body.alias)
+            });
+            return Err((StatusCode::CONFLICT, Json(error_response)));
+        }
+        Err(e) => return Err(internal_error(e)),
+    }
+
+    let genre = find_genre(&data.db, &genre.genre_name).await?;
+
+    println!("POST: added alias '{}' to {}", body.alias, genre.genre_name);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "genre": genre,
+        })),
+    ))
+}
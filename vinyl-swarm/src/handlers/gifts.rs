@@ -0,0 +1,292 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    handlers::{internal_error, users::find_user},
+    models::gift::{
+        GiftReservationModel, ReserveGiftSchema, SharedWishlistRecordModel, WishlistShareModel,
+    },
+    AppState,
+};
+
+/// share_path:
+/// where visitors open a shared wishlist
+fn share_path(share_token: &str) -> String {
+    format!("/api/wishlists/{}", share_token)
+}
+
+/// find_shared_wishlist_owner:
+/// the id and name of the user a share token belongs to, NOT_FOUND for a token
+/// that never existed or was revoked
+async fn find_shared_wishlist_owner(
+    db: &Pool<Postgres>,
+    share_token: &str,
+) -> Result<(Uuid, String), (StatusCode, Json<serde_json::Value>)> {
+    let owner = sqlx::query!(
+        "SELECT u.user_id, u.user_name FROM wishlist_share_links l
+        JOIN users u USING (user_id) WHERE l.share_token = $1",
+        share_token
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(internal_error)?;
+
+    owner
+        .map(|owner| (owner.user_id, owner.user_name))
+        .ok_or_else(|| {
+            let error_response = json!({
+                "status": "fail",
+                "message": "this wishlist link doesn't exist or was turned off"
+            });
+            (StatusCode::NOT_FOUND, Json(error_response))
+        })
+}
+
+/// GET a user's wishlist share link
+pub async fn get_wishlist_share(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = find_user(&data.db, user_id).await?;
+
+    let share = sqlx::query!(
+        "SELECT share_token, created_at FROM wishlist_share_links WHERE user_id = $1",
+        user_id
+    )
+    .fetch_optional(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(share) = share else {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("{} hasn't shared their wishlist", user.user_name)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    println!("GET: wishlist share link for user_id: {}", user_id);
+
+    Ok(Json(json!({
+        "status": "success",
+        "share": WishlistShareModel {
+            path: share_path(&share.share_token),
+            share_token: share.share_token,
+            created_at: share.created_at,
+        },
+    })))
+}
+
+/// POST share a user's wishlist. a user who already shared it gets a new
+/// token and the old link stops working
+pub async fn create_wishlist_share(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = find_user(&data.db, user_id).await?;
+
+    let share = sqlx::query!(
+        "INSERT INTO wishlist_share_links (user_id) VALUES ($1)
+        ON CONFLICT (user_id) DO UPDATE
+        SET share_token = encode(gen_random_bytes(24), 'hex'), created_at = NOW()
+        RETURNING share_token, created_at",
+        user_id
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!("POST: {} shared their wishlist", user.user_name);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "share": WishlistShareModel {
+                path: share_path(&share.share_token),
+                share_token: share.share_token,
+                created_at: share.created_at,
+            },
+        })),
+    ))
+}
+
+/// DELETE stop sharing a user's wishlist, gifts already reserved stay reserved
+pub async fn revoke_wishlist_share(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let delete_query = sqlx::query!(
+        "DELETE FROM wishlist_share_links WHERE user_id = $1",
+        user_id
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?
+    .rows_affected();
+
+    if delete_query == 0 {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("user {} hasn't shared their wishlist", user_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    }
+
+    println!("DELETE: user {} stopped sharing their wishlist", user_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET a shared wishlist, read-only and without an account.
+/// records someone has already reserved are left out
+pub async fn get_shared_wishlist(
+    Path(share_token): Path<String>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let (user_id, user_name) = find_shared_wishlist_owner(&data.db, &share_token).await?;
+
+    let records = sqlx::query_as::<_, SharedWishlistRecordModel>(
+        "SELECT w.added_at, to_jsonb(r) AS record
+        FROM user_wishlist w JOIN records r USING (record_id)
+        WHERE w.user_id = $1 AND NOT EXISTS (
+            SELECT 1 FROM gift_reservations g WHERE g.user_wish_list_id = w.user_wish_list_id
+        )
+        ORDER BY w.added_at, r.artist",
+    )
+    .bind(user_id)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} unreserved records on {}'s shared wishlist",
+        records.len(),
+        user_name
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "user_name": user_name,
+        "results": records.len(),
+        "records": records,
+    })))
+}
+
+/// POST reserve a record on a shared wishlist as a gift. it disappears for other
+/// visitors, the owner is never told
+pub async fn reserve_gift(
+    Path(share_token): Path<String>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<ReserveGiftSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if body.reserver_name.trim().is_empty() {
+        let error_response = json!({
+            "status": "fail",
+            "message": "reserver_name can't be empty"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let (user_id, user_name) = find_shared_wishlist_owner(&data.db, &share_token).await?;
+
+    let user_wish_list_id = sqlx::query_scalar!(
+        "SELECT user_wish_list_id FROM user_wishlist WHERE user_id = $1 AND record_id = $2",
+        user_id,
+        body.record_id
+    )
+    .fetch_optional(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(user_wish_list_id) = user_wish_list_id else {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("record_id {} isn't on {}'s wishlist", body.record_id, user_name)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    let insert_result = sqlx::query!(
+        "INSERT INTO gift_reservations (user_wish_list_id, reserver_name, note)
+        VALUES ($1, $2, $3)
+        RETURNING reservation_id, reserver_name, note, cancel_token, created_at",
+        user_wish_list_id,
+        body.reserver_name.trim(),
+        body.note
+    )
+    .fetch_one(&data.db)
+    .await;
+
+    match insert_result {
+        Ok(reservation) => {
+            println!(
+                "POST: record {} reserved as a gift for {}",
+                body.record_id, user_name
+            );
+
+            Ok((
+                StatusCode::CREATED,
+                Json(json!({
+                    "status": "success",
+                    "reservation": GiftReservationModel {
+                        reservation_id: reservation.reservation_id,
+                        record_id: body.record_id,
+                        reserver_name: reservation.reserver_name,
+                        note: reservation.note,
+                        cancel_token: reservation.cancel_token,
+                        created_at: reservation.created_at,
+                    },
+                })),
+            ))
+        }
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            let error_response = json!({
+                "status": "fail",
+                "message": "someone has already reserved this record"
+            });
+            Err((StatusCode::CONFLICT, Json(error_response)))
+        }
+        Err(e) => Err(internal_error(e)),
+    }
+}
+
+/// DELETE cancel a gift reservation with the cancel_token handed out when it was made,
+/// the record shows up for other visitors again
+pub async fn cancel_gift_reservation(
+    Path((share_token, cancel_token)): Path<(String, String)>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let (user_id, _) = find_shared_wishlist_owner(&data.db, &share_token).await?;
+
+    let delete_query = sqlx::query!(
+        "DELETE FROM gift_reservations g USING user_wishlist w
+        WHERE g.user_wish_list_id = w.user_wish_list_id
+        AND w.user_id = $1 AND g.cancel_token = $2",
+        user_id,
+        cancel_token
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?
+    .rows_affected();
+
+    if delete_query == 0 {
+        let error_response = json!({
+            "status": "fail",
+            "message": "reservation not found"
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    }
+
+    println!("DELETE: cancelled a gift reservation for user {}", user_id);
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,306 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use sqlx::{types::BigDecimal, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::{
+    handlers::{
+        alerts::match_wishlist_alerts,
+        internal_error,
+        record_stores::check_store_exists,
+        records::check_record_exists,
+        store_claims::{check_store_manager, store_owner_ids},
+        users::find_user,
+    },
+    models::inventory::{
+        CreateListingSchema, DeleteListingOptions, FilterOptions, InventoryListingModel,
+        RecordAvailabilityModel, StoreStockModel, UpdateListingSchema, LISTING_CONDITIONS,
+    },
+    AppState,
+};
+
+/// check_listing:
+/// conditions have to be a goldmine grade, prices and quantities can't be negative
+fn check_listing(
+    condition: Option<&str>,
+    price: Option<&BigDecimal>,
+    quantity: Option<i32>,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let message = if condition.is_some_and(|condition| !LISTING_CONDITIONS.contains(&condition)) {
+        format!("condition must be one of {}", LISTING_CONDITIONS.join(", "))
+    } else if price.is_some_and(|price| *price < BigDecimal::from(0)) {
+        "price can't be negative".to_string()
+    } else if quantity.is_some_and(|quantity| quantity < 0) {
+        "quantity can't be negative".to_string()
+    } else {
+        return Ok(());
+    };
+
+    Err((
+        StatusCode::BAD_REQUEST,
+        Json(json!({"status": "fail", "message": message})),
+    ))
+}
+
+/// GET a store's current stock, records that sold out are left off
+pub async fn get_store_inventory(
+    Path(record_store_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_store_exists(&data.db, record_store_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let stock = sqlx::query_as::<_, StoreStockModel>(
+        "SELECT i.*, to_jsonb(r) AS record FROM store_inventory i JOIN records r USING (record_id)
+        WHERE i.record_store_id = $1 AND i.quantity > 0
+        ORDER BY r.artist, r.title, i.price LIMIT $2 OFFSET $3",
+    )
+    .bind(record_store_id)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} listings for record store {}",
+        stock.len(),
+        record_store_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": stock.len(),
+        "inventory": stock,
+    })))
+}
+
+/// POST list a record at a store, or report a copy spotted there.
+/// the same record in the same condition updates the existing listing.
+/// stores with verified owners only take listings from their owners
+pub async fn add_store_listing(
+    Path(record_store_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<CreateListingSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_listing(Some(&body.condition), Some(&body.price), body.quantity)?;
+    let reporter = find_user(&data.db, body.reported_by).await?;
+    check_store_exists(&data.db, record_store_id).await?;
+    check_store_manager(&data.db, record_store_id, &reporter).await?;
+
+    check_record_exists(&data.db, body.record_id).await?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let listing = sqlx::query_as!(
+        InventoryListingModel,
+        "INSERT INTO store_inventory (record_store_id, record_id, price, condition, quantity, reported_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (record_store_id, record_id, condition) DO UPDATE
+        SET price = EXCLUDED.price, quantity = EXCLUDED.quantity,
+            reported_by = EXCLUDED.reported_by, updated_at = NOW()
+        RETURNING *",
+        record_store_id,
+        body.record_id,
+        body.price,
+        body.condition,
+        body.quantity.unwrap_or(1),
+        body.reported_by
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let alerts_sent = match_wishlist_alerts(&mut *tx, Some(listing.listing_id), None)
+        .await
+        .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    println!(
+        "POST: listed record {} at record store {}",
+        listing.record_id, record_store_id
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "listing": listing,
+            "alerts_sent": alerts_sent,
+        })),
+    ))
+}
+
+/// PATCH a listing's price, condition or quantity
+pub async fn edit_store_listing(
+    Path((record_store_id, listing_id)): Path<(Uuid, Uuid)>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<UpdateListingSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_listing(
+        body.condition.as_deref(),
+        body.price.as_ref(),
+        body.quantity,
+    )?;
+    let reporter = find_user(&data.db, body.reported_by).await?;
+    check_store_manager(&data.db, record_store_id, &reporter).await?;
+
+    let mut update_query = QueryBuilder::<Postgres>::new("UPDATE store_inventory SET ");
+    let mut columns = update_query.separated(", ");
+
+    columns
+        .push("reported_by = ")
+        .push_bind_unseparated(body.reported_by);
+    columns.push("updated_at = NOW()");
+    if let Some(price) = &body.price {
+        columns.push("price = ").push_bind_unseparated(price);
+    }
+    if let Some(condition) = &body.condition {
+        columns
+            .push("condition = ")
+            .push_bind_unseparated(condition);
+    }
+    if let Some(quantity) = body.quantity {
+        columns.push("quantity = ").push_bind_unseparated(quantity);
+    }
+
+    update_query
+        .push(" WHERE listing_id = ")
+        .push_bind(listing_id)
+        .push(" AND record_store_id = ")
+        .push_bind(record_store_id)
+        .push(" RETURNING *");
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let query_result = update_query
+        .build_query_as::<InventoryListingModel>()
+        .fetch_optional(&mut *tx)
+        .await;
+
+    match query_result {
+        Ok(Some(listing)) => {
+            // restocks and price drops can bring a listing under someone's target price
+            let alerts_sent = match_wishlist_alerts(&mut *tx, Some(listing_id), None)
+                .await
+                .map_err(internal_error)?;
+
+            tx.commit().await.map_err(internal_error)?;
+
+            println!("PATCH: updated listing {}", listing_id);
+
+            Ok(Json(json!({
+                "status": "success",
+                "listing": listing,
+                "alerts_sent": alerts_sent,
+            })))
+        }
+        Ok(None) => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("listing_id {} not found at record store {}", listing_id, record_store_id)
+            });
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        // regrading onto a condition the store already lists
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("the store already lists this record in {} condition", body.condition.unwrap_or_default())
+            });
+            Err((StatusCode::CONFLICT, Json(error_response)))
+        }
+        Err(e) => Err(internal_error(e)),
+    }
+}
+
+/// DELETE a listing from a store's inventory,
+/// stores with verified owners need one of them (or a moderator) as the user_id
+pub async fn delete_store_listing(
+    Path((record_store_id, listing_id)): Path<(Uuid, Uuid)>,
+    Query(opts): Query<DeleteListingOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    match opts.user_id {
+        Some(user_id) => {
+            let user = find_user(&data.db, user_id).await?;
+            check_store_manager(&data.db, record_store_id, &user).await?;
+        }
+        None => {
+            let owner_ids = store_owner_ids(&data.db, record_store_id)
+                .await
+                .map_err(internal_error)?;
+
+            if !owner_ids.is_empty() {
+                let error_response = json!({
+                    "status": "fail",
+                    "message": format!("record store {} is managed by its verified owners, pass their user_id", record_store_id)
+                });
+                return Err((StatusCode::FORBIDDEN, Json(error_response)));
+            }
+        }
+    }
+
+    let delete_query = sqlx::query!(
+        "DELETE FROM store_inventory WHERE listing_id = $1 AND record_store_id = $2",
+        listing_id,
+        record_store_id
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?
+    .rows_affected();
+
+    if delete_query == 0 {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("listing_id {} not found at record store {}", listing_id, record_store_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    }
+
+    println!("DELETE: removed listing: {}", listing_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET every store with a record in stock, cheapest first
+pub async fn get_record_availability(
+    Path(record_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_record_exists(&data.db, record_id).await?;
+
+    let availability = sqlx::query_as::<_, RecordAvailabilityModel>(
+        "SELECT i.*, to_jsonb(s) AS record_store
+        FROM store_inventory i JOIN record_stores s USING (record_store_id)
+        WHERE i.record_id = $1 AND i.quantity > 0
+        ORDER BY i.price, s.store_name",
+    )
+    .bind(record_id)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: record {} is in stock at {} listings",
+        record_id,
+        availability.len()
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": availability.len(),
+        "availability": availability,
+    })))
+}
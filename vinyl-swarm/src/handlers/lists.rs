@@ -0,0 +1,575 @@
+use std::{collections::HashSet, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use sqlx::{PgExecutor, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::{
+    handlers::{internal_error, records::check_record_exists, users::find_user},
+    models::list::{
+        AddListRecordSchema, CopyRecordListSchema, CreateRecordListSchema, ListFilterOptions,
+        ListOwnerOptions, ListRecordModel, RecordListModel, ReorderListSchema,
+        UpdateRecordListSchema, ViewListOptions, LIST_VISIBILITIES,
+    },
+    AppState,
+};
+
+/// check_list:
+/// BAD_REQUEST for an empty title or an unknown visibility
+fn check_list(
+    title: Option<&str>,
+    visibility: Option<&str>,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if title.is_some_and(|title| title.trim().is_empty()) {
+        let error_response = json!({
+            "status": "fail",
+            "message": "a list needs a title"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    if let Some(visibility) = visibility {
+        if !LIST_VISIBILITIES.contains(&visibility) {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("visibility must be one of {}", LIST_VISIBILITIES.join(", "))
+            });
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+    }
+
+    Ok(())
+}
+
+/// fetch_record_list:
+/// a list along with its owner's name and how many records are on it
+async fn fetch_record_list<'e>(
+    executor: impl PgExecutor<'e>,
+    list_id: Uuid,
+) -> Result<RecordListModel, (StatusCode, Json<serde_json::Value>)> {
+    let list = sqlx::query_as!(
+        RecordListModel,
+        r#"SELECT l.list_id, l.user_id, u.user_name, l.title, l.description, l.visibility,
+            l.copied_from_list_id,
+            (SELECT COUNT(*) FROM record_list_items i WHERE i.list_id = l.list_id) AS "record_count!",
+            l.created_at, l.updated_at
+        FROM record_lists l JOIN users u ON u.user_id = l.user_id
+        WHERE l.list_id = $1"#,
+        list_id
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(internal_error)?;
+
+    list.ok_or_else(|| {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("list_id {} not found", list_id)
+        });
+        (StatusCode::NOT_FOUND, Json(error_response))
+    })
+}
+
+/// fetch_list_records:
+/// the records on a list in order
+async fn fetch_list_records<'e>(
+    executor: impl PgExecutor<'e>,
+    list_id: Uuid,
+) -> Result<Vec<ListRecordModel>, sqlx::Error> {
+    sqlx::query_as::<_, ListRecordModel>(
+        "SELECT i.position, i.note, i.added_at, to_jsonb(r) AS record
+        FROM record_list_items i JOIN records r USING (record_id)
+        WHERE i.list_id = $1
+        ORDER BY i.position",
+    )
+    .bind(list_id)
+    .fetch_all(executor)
+    .await
+}
+
+/// check_list_owner:
+/// FORBIDDEN unless user_id owns the list
+fn check_list_owner(
+    list: &RecordListModel,
+    user_id: Uuid,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if list.user_id != user_id {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("only {} can change the list {}", list.user_name, list.title)
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    Ok(())
+}
+
+/// check_list_visible:
+/// FORBIDDEN when a private list is looked at by anyone but its owner
+fn check_list_visible(
+    list: &RecordListModel,
+    viewer_id: Option<Uuid>,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if list.visibility == "private" && viewer_id != Some(list.user_id) {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("the list {} isn't visible to you", list.title)
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    Ok(())
+}
+
+/// GET a user's lists, most recently updated first
+pub async fn get_user_lists(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<ListFilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_user(&data.db, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let lists = sqlx::query_as!(
+        RecordListModel,
+        r#"SELECT l.list_id, l.user_id, u.user_name, l.title, l.description, l.visibility,
+            l.copied_from_list_id,
+            (SELECT COUNT(*) FROM record_list_items i WHERE i.list_id = l.list_id) AS "record_count!",
+            l.created_at, l.updated_at
+        FROM record_lists l JOIN users u ON u.user_id = l.user_id
+        WHERE l.user_id = $1 AND (l.visibility = 'public' OR l.user_id = $2)
+        ORDER BY l.updated_at DESC LIMIT $3 OFFSET $4"#,
+        user_id,
+        opts.viewer_id,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} lists for user_id: {}",
+        lists.len(),
+        user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": lists.len(),
+        "lists": lists,
+    })))
+}
+
+/// POST create a list for a user
+pub async fn create_record_list(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<CreateRecordListSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_list(Some(&body.title), body.visibility.as_deref())?;
+
+    let user = find_user(&data.db, user_id).await?;
+
+    let list_id = sqlx::query_scalar!(
+        "INSERT INTO record_lists (user_id, title, description, visibility)
+        VALUES ($1, $2, $3, $4) RETURNING list_id",
+        user_id,
+        body.title.trim(),
+        body.description,
+        body.visibility.as_deref().unwrap_or("private")
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let list = fetch_record_list(&data.db, list_id).await?;
+
+    println!("POST: {} created the list {}", user.user_name, list.title);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "list": list,
+        })),
+    ))
+}
+
+/// GET a list and its records, in order
+pub async fn get_record_list(
+    Path(list_id): Path<Uuid>,
+    Query(opts): Query<ViewListOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let list = fetch_record_list(&data.db, list_id).await?;
+    check_list_visible(&list, opts.viewer_id)?;
+
+    let records = fetch_list_records(&data.db, list_id)
+        .await
+        .map_err(internal_error)?;
+
+    println!(
+        "GET: returning the list {} with {} records",
+        list.title,
+        records.len()
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "list": list,
+        "records": records,
+    })))
+}
+
+/// PATCH a list's title, description or visibility
+pub async fn edit_record_list(
+    Path(list_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<UpdateRecordListSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_list(body.title.as_deref(), body.visibility.as_deref())?;
+
+    let list = fetch_record_list(&data.db, list_id).await?;
+    check_list_owner(&list, body.user_id)?;
+
+    let mut update_query = QueryBuilder::<Postgres>::new("UPDATE record_lists SET ");
+    let mut columns = update_query.separated(", ");
+
+    columns.push("updated_at = NOW()");
+    if let Some(title) = &body.title {
+        columns.push("title = ").push_bind_unseparated(title.trim());
+    }
+    if let Some(description) = &body.description {
+        columns
+            .push("description = ")
+            .push_bind_unseparated(description);
+    }
+    if let Some(visibility) = &body.visibility {
+        columns
+            .push("visibility = ")
+            .push_bind_unseparated(visibility);
+    }
+
+    update_query.push(" WHERE list_id = ").push_bind(list_id);
+
+    update_query
+        .build()
+        .execute(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    let list = fetch_record_list(&data.db, list_id).await?;
+
+    println!("PATCH: updated list {}", list_id);
+
+    Ok(Json(json!({
+        "status": "success",
+        "list": list,
+    })))
+}
+
+/// DELETE a list, by its owner
+pub async fn delete_record_list(
+    Path(list_id): Path<Uuid>,
+    Query(opts): Query<ListOwnerOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let list = fetch_record_list(&data.db, list_id).await?;
+    check_list_owner(&list, opts.user_id)?;
+
+    sqlx::query!("DELETE FROM record_lists WHERE list_id = $1", list_id)
+        .execute(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    println!("DELETE: removed list: {}", list_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// POST add a record to a list, at the end or at the position given
+pub async fn add_list_record(
+    Path(list_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<AddListRecordSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if body.position.is_some_and(|position| position < 1) {
+        let error_response = json!({
+            "status": "fail",
+            "message": "position starts at 1"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let list = fetch_record_list(&data.db, list_id).await?;
+    check_list_owner(&list, body.user_id)?;
+    check_record_exists(&data.db, body.record_id).await?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    // the list is locked so records added at the same time don't share a position
+    let last_position = sqlx::query_scalar!(
+        r#"SELECT COUNT(i.record_id)::INTEGER AS "last_position!"
+        FROM (SELECT list_id FROM record_lists WHERE list_id = $1 FOR UPDATE) l
+        LEFT JOIN record_list_items i USING (list_id)"#,
+        list_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    // positions past the end of the list just append
+    let position = body.position.map_or(last_position + 1, |position| {
+        position.min(last_position + 1)
+    });
+
+    sqlx::query!(
+        "UPDATE record_list_items SET position = position + 1
+        WHERE list_id = $1 AND position >= $2",
+        list_id,
+        position
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let insert_result = sqlx::query!(
+        "INSERT INTO record_list_items (list_id, record_id, position, note)
+        VALUES ($1, $2, $3, $4)",
+        list_id,
+        body.record_id,
+        position,
+        body.note
+    )
+    .execute(&mut *tx)
+    .await;
+
+    match insert_result {
+        Ok(_) => {}
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("record_id {} is already on the list {}", body.record_id, list.title)
+            });
+            return Err((StatusCode::CONFLICT, Json(error_response)));
+        }
+        Err(e) => return Err(internal_error(e)),
+    }
+
+    sqlx::query!(
+        "UPDATE record_lists SET updated_at = NOW() WHERE list_id = $1",
+        list_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let records = fetch_list_records(&mut *tx, list_id)
+        .await
+        .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    println!(
+        "POST: added record {} to list {} at position {}",
+        body.record_id, list_id, position
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "list_id": list_id,
+            "records": records,
+        })),
+    ))
+}
+
+/// DELETE take a record off a list, the records after it move up
+pub async fn remove_list_record(
+    Path((list_id, record_id)): Path<(Uuid, Uuid)>,
+    Query(opts): Query<ListOwnerOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let list = fetch_record_list(&data.db, list_id).await?;
+    check_list_owner(&list, opts.user_id)?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let removed_position = sqlx::query_scalar!(
+        "DELETE FROM record_list_items WHERE list_id = $1 AND record_id = $2 RETURNING position",
+        list_id,
+        record_id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(removed_position) = removed_position else {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("record_id {} isn't on the list {}", record_id, list.title)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    sqlx::query!(
+        "UPDATE record_list_items SET position = position - 1
+        WHERE list_id = $1 AND position > $2",
+        list_id,
+        removed_position
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    sqlx::query!(
+        "UPDATE record_lists SET updated_at = NOW() WHERE list_id = $1",
+        list_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    println!("DELETE: removed record {} from list {}", record_id, list_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// PUT reorder a list, record_ids has to list every record on it exactly once
+pub async fn reorder_record_list(
+    Path(list_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<ReorderListSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let list = fetch_record_list(&data.db, list_id).await?;
+    check_list_owner(&list, body.user_id)?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let current_ids: HashSet<Uuid> = sqlx::query_scalar!(
+        "SELECT record_id FROM record_list_items WHERE list_id = $1 FOR UPDATE",
+        list_id
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(internal_error)?
+    .into_iter()
+    .collect();
+
+    let new_ids: HashSet<Uuid> = body.record_ids.iter().copied().collect();
+
+    if new_ids.len() != body.record_ids.len() || new_ids != current_ids {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!(
+                "record_ids must list each of the {} records on the list exactly once",
+                current_ids.len()
+            )
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    sqlx::query!(
+        "UPDATE record_list_items i SET position = o.position
+        FROM unnest($2::uuid[]) WITH ORDINALITY AS o (record_id, position)
+        WHERE i.list_id = $1 AND i.record_id = o.record_id",
+        list_id,
+        &body.record_ids
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    sqlx::query!(
+        "UPDATE record_lists SET updated_at = NOW() WHERE list_id = $1",
+        list_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let records = fetch_list_records(&mut *tx, list_id)
+        .await
+        .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    println!("PUT: reordered list {}", list_id);
+
+    Ok(Json(json!({
+        "status": "success",
+        "list_id": list_id,
+        "records": records,
+    })))
+}
+
+/// POST copy a public list, or one of the user's own, into the user's lists
+pub async fn copy_record_list(
+    Path(list_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<CopyRecordListSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_list(body.title.as_deref(), None)?;
+
+    let user = find_user(&data.db, body.user_id).await?;
+    let source = fetch_record_list(&data.db, list_id).await?;
+
+    if source.visibility != "public" && source.user_id != body.user_id {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("only public lists can be copied, {} isn't", source.title)
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let copy_id = sqlx::query_scalar!(
+        "INSERT INTO record_lists (user_id, title, description, copied_from_list_id)
+        VALUES ($1, $2, $3, $4) RETURNING list_id",
+        body.user_id,
+        body.title.as_deref().unwrap_or(&source.title).trim(),
+        source.description,
+        list_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    sqlx::query!(
+        "INSERT INTO record_list_items (list_id, record_id, position, note)
+        SELECT $1, record_id, position, note FROM record_list_items WHERE list_id = $2",
+        copy_id,
+        list_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let copy = fetch_record_list(&mut *tx, copy_id).await?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    println!(
+        "POST: {} copied the list {} by {}",
+        user.user_name, source.title, source.user_name
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "list": copy,
+        })),
+    ))
+}
@@ -0,0 +1,346 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{NaiveDate, Utc};
+use serde_json::json;
+use sqlx::{PgExecutor, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::{
+    handlers::{internal_error, plays::find_collection_item, users::find_user},
+    models::loan::{
+        CreateLoanSchema, FilterOptions, LoanFilterOptions, LoanModel, ReturnLoanSchema,
+        UpdateLoanSchema, LOAN_STATUSES,
+    },
+    AppState,
+};
+
+/// loans as read into LoanModel, callers add their own WHERE clause
+const LOAN_QUERY: &str = "SELECT l.loan_id, l.borrower_id, l.borrower_name, l.lent_date,
+        l.due_date, l.returned_date, l.notes,
+        COALESCE(l.returned_date IS NULL AND l.due_date < CURRENT_DATE, FALSE) AS overdue,
+        l.created_at, to_jsonb(r) AS record
+    FROM record_loans l
+    JOIN user_records ur ON ur.user_record_id = l.user_record_id
+    JOIN records r ON r.record_id = ur.record_id";
+
+/// find_loan:
+/// one of a user's loans, NOT_FOUND when the loan isn't theirs
+async fn find_loan<'e>(
+    executor: impl PgExecutor<'e>,
+    user_id: Uuid,
+    loan_id: Uuid,
+) -> Result<LoanModel, (StatusCode, Json<serde_json::Value>)> {
+    let loan = sqlx::query_as::<_, LoanModel>(&format!(
+        "{} WHERE ur.user_id = $1 AND l.loan_id = $2",
+        LOAN_QUERY
+    ))
+    .bind(user_id)
+    .bind(loan_id)
+    .fetch_optional(executor)
+    .await
+    .map_err(internal_error)?;
+
+    loan.ok_or_else(|| {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("loan_id {} not found for user {}", loan_id, user_id)
+        });
+        (StatusCode::NOT_FOUND, Json(error_response))
+    })
+}
+
+/// check_loan_dates:
+/// BAD_REQUEST for a due or returned date before the record was lent
+fn check_loan_dates(
+    lent_date: NaiveDate,
+    later_date: Option<NaiveDate>,
+    field: &str,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if later_date.is_some_and(|later_date| later_date < lent_date) {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("{} can't be before the lent_date {}", field, lent_date)
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    Ok(())
+}
+
+/// check_not_future:
+/// BAD_REQUEST for a lent or returned date that hasn't happened yet
+fn check_not_future(
+    date: Option<NaiveDate>,
+    field: &str,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if date.is_some_and(|date| date > Utc::now().date_naive()) {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("{} can't be in the future", field)
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    Ok(())
+}
+
+/// GET a user's loans, the records still out by default, most recently lent first
+pub async fn get_user_loans(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<LoanFilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let status = opts.status.as_deref().unwrap_or("out");
+
+    if !LOAN_STATUSES.contains(&status) {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("status must be one of {}", LOAN_STATUSES.join(", "))
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let user = find_user(&data.db, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let loans = sqlx::query_as::<_, LoanModel>(&format!(
+        "{} WHERE ur.user_id = $1
+        AND ($2 = 'all' OR (l.returned_date IS NULL) = ($2 = 'out'))
+        ORDER BY l.lent_date DESC, l.created_at DESC LIMIT $3 OFFSET $4",
+        LOAN_QUERY
+    ))
+    .bind(user_id)
+    .bind(status)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} {} loans for {}",
+        loans.len(),
+        status,
+        user.user_name
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": loans.len(),
+        "loans": loans,
+    })))
+}
+
+/// POST lend a record from the user's collection.
+/// a record that's already out has to come back before it can be lent again
+pub async fn lend_record(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<CreateLoanSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = find_user(&data.db, user_id).await?;
+
+    // who has the record, by account or by name but not both
+    let borrower_name = match (body.borrower_id, body.borrower_name.as_deref()) {
+        (Some(borrower_id), None) if borrower_id == user_id => {
+            let error_response = json!({
+                "status": "fail",
+                "message": "users can't lend records to themselves"
+            });
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+        (Some(borrower_id), None) => find_user(&data.db, borrower_id).await?.user_name,
+        (None, Some(borrower_name)) if !borrower_name.trim().is_empty() => {
+            borrower_name.trim().to_string()
+        }
+        _ => {
+            let error_response = json!({
+                "status": "fail",
+                "message": "a loan needs either a borrower_id or a borrower_name"
+            });
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+    };
+
+    check_not_future(body.lent_date, "lent_date")?;
+    let lent_date = body.lent_date.unwrap_or_else(|| Utc::now().date_naive());
+    check_loan_dates(lent_date, body.due_date, "due_date")?;
+
+    let user_record_id = find_collection_item(&data.db, user_id, body.record_id).await?;
+
+    let insert_result = sqlx::query_scalar!(
+        "INSERT INTO record_loans (user_record_id, borrower_id, borrower_name, lent_date, due_date, notes)
+        VALUES ($1, $2, $3, $4, $5, $6) RETURNING loan_id",
+        user_record_id,
+        body.borrower_id,
+        borrower_name,
+        lent_date,
+        body.due_date,
+        body.notes
+    )
+    .fetch_one(&data.db)
+    .await;
+
+    let loan_id = match insert_result {
+        Ok(loan_id) => loan_id,
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("record_id {} is already lent out", body.record_id)
+            });
+            return Err((StatusCode::CONFLICT, Json(error_response)));
+        }
+        Err(e) => return Err(internal_error(e)),
+    };
+
+    let loan = find_loan(&data.db, user_id, loan_id).await?;
+
+    println!(
+        "POST: {} lent {} to {}",
+        user.user_name, loan.record.title, loan.borrower_name
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "loan": loan,
+        })),
+    ))
+}
+
+/// PATCH change a loan's due date or notes
+pub async fn edit_loan(
+    Path((user_id, loan_id)): Path<(Uuid, Uuid)>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<UpdateLoanSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let loan = find_loan(&data.db, user_id, loan_id).await?;
+
+    if body.due_date.is_none() && body.notes.is_none() {
+        return Ok(Json(json!({
+            "status": "success",
+            "loan": loan,
+        })));
+    }
+
+    if let Some(due_date) = body.due_date {
+        check_loan_dates(loan.lent_date, due_date, "due_date")?;
+    }
+
+    let mut update_query = QueryBuilder::<Postgres>::new("UPDATE record_loans SET ");
+    let mut columns = update_query.separated(", ");
+
+    if let Some(due_date) = body.due_date {
+        columns.push("due_date = ").push_bind_unseparated(due_date);
+    }
+    if let Some(notes) = body.notes {
+        columns.push("notes = ").push_bind_unseparated(notes);
+    }
+
+    update_query.push(" WHERE loan_id = ").push_bind(loan_id);
+
+    update_query
+        .build()
+        .execute(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    let loan = find_loan(&data.db, user_id, loan_id).await?;
+
+    println!("PATCH: edited loan {}", loan_id);
+
+    Ok(Json(json!({
+        "status": "success",
+        "loan": loan,
+    })))
+}
+
+/// POST mark a lent record as back in the collection
+pub async fn return_loan(
+    Path((user_id, loan_id)): Path<(Uuid, Uuid)>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<ReturnLoanSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let loan = find_loan(&data.db, user_id, loan_id).await?;
+
+    if let Some(returned_date) = loan.returned_date {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("loan_id {} was already returned on {}", loan_id, returned_date)
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    check_not_future(body.returned_date, "returned_date")?;
+    let returned_date = body
+        .returned_date
+        .unwrap_or_else(|| Utc::now().date_naive());
+    check_loan_dates(loan.lent_date, Some(returned_date), "returned_date")?;
+
+    sqlx::query!(
+        "UPDATE record_loans SET returned_date = $1 WHERE loan_id = $2",
+        returned_date,
+        loan_id
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let loan = find_loan(&data.db, user_id, loan_id).await?;
+
+    println!(
+        "POST: {} returned {}",
+        loan.borrower_name, loan.record.title
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "loan": loan,
+    })))
+}
+
+/// GET the records a user lent out that are past their due date,
+/// longest overdue first
+pub async fn get_overdue_loans(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = find_user(&data.db, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let loans = sqlx::query_as::<_, LoanModel>(&format!(
+        "{} WHERE ur.user_id = $1 AND l.returned_date IS NULL AND l.due_date < CURRENT_DATE
+        ORDER BY l.due_date, l.lent_date LIMIT $2 OFFSET $3",
+        LOAN_QUERY
+    ))
+    .bind(user_id)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} overdue loans for {}",
+        loans.len(),
+        user.user_name
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": loans.len(),
+        "loans": loans,
+    })))
+}
@@ -0,0 +1,527 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use sqlx::{PgConnection, PgExecutor, Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    handlers::{
+        internal_error, notifications::notify, records::check_record_exists, users::find_user,
+    },
+    models::{
+        message::{
+            BlockModel, BlockUserSchema, ConversationModel, DirectMessageModel, FilterOptions,
+            SendMessageSchema, StartConversationSchema,
+        },
+        user::UserModel,
+    },
+    AppState,
+};
+
+/// messages along with their attached record, as read into DirectMessageModel.
+/// callers add their own WHERE clause
+const MESSAGE_QUERY: &str = "SELECT m.message_id, m.conversation_id, m.sender_id, m.body,
+        m.created_at, m.read_at,
+        CASE WHEN m.record_id IS NULL THEN 'null'::jsonb ELSE to_jsonb(r) END AS record
+    FROM direct_messages m
+    LEFT JOIN records r ON r.record_id = m.record_id";
+
+/// is_blocked:
+/// whether either user has blocked the other
+pub async fn is_blocked<'e>(
+    executor: impl PgExecutor<'e>,
+    user_id: Uuid,
+    other_user_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS (
+            SELECT 1 FROM user_blocks
+            WHERE (blocker_id = $1 AND blocked_id = $2) OR (blocker_id = $2 AND blocked_id = $1)
+        ) AS "blocked!""#,
+        user_id,
+        other_user_id
+    )
+    .fetch_one(executor)
+    .await
+}
+
+/// check_message_body:
+/// BAD_REQUEST for a message with nothing in it
+fn check_message_body(body: &str) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if body.trim().is_empty() {
+        let error_response = json!({
+            "status": "fail",
+            "message": "a message can't be empty"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    Ok(())
+}
+
+/// check_can_message:
+/// FORBIDDEN when either user has blocked the other
+async fn check_can_message(
+    db: &Pool<Postgres>,
+    sender: &UserModel,
+    recipient: &UserModel,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if is_blocked(db, sender.user_id, recipient.user_id)
+        .await
+        .map_err(internal_error)?
+    {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("{} can't message {}", sender.user_name, recipient.user_name)
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    Ok(())
+}
+
+/// find_other_participant:
+/// the other user in a conversation, NOT_FOUND unless user_id is one of its participants
+async fn find_other_participant(
+    db: &Pool<Postgres>,
+    conversation_id: Uuid,
+    user_id: Uuid,
+) -> Result<Uuid, (StatusCode, Json<serde_json::Value>)> {
+    let other_user_id = sqlx::query_scalar!(
+        r#"SELECT CASE WHEN user_one_id = $2 THEN user_two_id ELSE user_one_id END AS "other_user_id!"
+        FROM conversations
+        WHERE conversation_id = $1 AND $2 IN (user_one_id, user_two_id)"#,
+        conversation_id,
+        user_id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(internal_error)?;
+
+    other_user_id.ok_or_else(|| {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("conversation_id {} not found for user {}", conversation_id, user_id)
+        });
+        (StatusCode::NOT_FOUND, Json(error_response))
+    })
+}
+
+/// insert_message:
+/// adds a message to a conversation and bumps the conversation to the top of the inbox
+async fn insert_message(
+    conn: &mut PgConnection,
+    conversation_id: Uuid,
+    sender_id: Uuid,
+    body: &str,
+    record_id: Option<Uuid>,
+) -> Result<DirectMessageModel, sqlx::Error> {
+    let message_id = sqlx::query_scalar!(
+        "INSERT INTO direct_messages (conversation_id, sender_id, body, record_id)
+        VALUES ($1, $2, $3, $4) RETURNING message_id",
+        conversation_id,
+        sender_id,
+        body.trim(),
+        record_id
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE conversations SET last_message_at = NOW() WHERE conversation_id = $1",
+        conversation_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query_as::<_, DirectMessageModel>(&format!("{} WHERE m.message_id = $1", MESSAGE_QUERY))
+        .bind(message_id)
+        .fetch_one(&mut *conn)
+        .await
+}
+
+/// notify_recipient:
+/// lets the recipient know a message is waiting
+async fn notify_recipient(
+    db: &Pool<Postgres>,
+    sender: &UserModel,
+    recipient_id: Uuid,
+    conversation_id: Uuid,
+) {
+    notify(
+        db,
+        recipient_id,
+        "direct_message",
+        &format!("{} sent you a message", sender.user_name),
+        Some(sender.user_id),
+        Some(conversation_id),
+    )
+    .await;
+}
+
+/// GET a user's conversations, the most recently active first, with unread counts
+pub async fn get_conversations(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_user(&data.db, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let conversations = sqlx::query_as!(
+        ConversationModel,
+        r#"SELECT c.conversation_id, o.user_id, o.user_name, c.created_at, c.last_message_at,
+            (SELECT m.body FROM direct_messages m WHERE m.conversation_id = c.conversation_id
+                ORDER BY m.created_at DESC LIMIT 1) AS last_message,
+            (SELECT COUNT(*) FROM direct_messages m WHERE m.conversation_id = c.conversation_id
+                AND m.sender_id <> $1 AND m.read_at IS NULL) AS "unread!"
+        FROM conversations c
+        JOIN users o ON o.user_id = CASE WHEN c.user_one_id = $1 THEN c.user_two_id ELSE c.user_one_id END
+        WHERE $1 IN (c.user_one_id, c.user_two_id)
+        ORDER BY c.last_message_at DESC LIMIT $2 OFFSET $3"#,
+        user_id,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let unread = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "unread!"
+        FROM direct_messages m JOIN conversations c USING (conversation_id)
+        WHERE $1 IN (c.user_one_id, c.user_two_id) AND m.sender_id <> $1 AND m.read_at IS NULL"#,
+        user_id
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} conversations for user_id: {}",
+        conversations.len(),
+        user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": conversations.len(),
+        "unread": unread,
+        "conversations": conversations,
+    })))
+}
+
+/// POST send a message to another user, starting a conversation with them
+/// or carrying on the one they already have
+pub async fn start_conversation(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<StartConversationSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_message_body(&body.body)?;
+
+    if user_id == body.recipient_id {
+        let error_response = json!({
+            "status": "fail",
+            "message": "users can't message themselves"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let sender = find_user(&data.db, user_id).await?;
+    let recipient = find_user(&data.db, body.recipient_id).await?;
+    check_can_message(&data.db, &sender, &recipient).await?;
+
+    if let Some(record_id) = body.record_id {
+        check_record_exists(&data.db, record_id).await?;
+    }
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let conversation_id = sqlx::query_scalar!(
+        "INSERT INTO conversations (user_one_id, user_two_id)
+        VALUES (LEAST($1::uuid, $2::uuid), GREATEST($1::uuid, $2::uuid))
+        ON CONFLICT (user_one_id, user_two_id) DO UPDATE SET user_one_id = EXCLUDED.user_one_id
+        RETURNING conversation_id",
+        user_id,
+        body.recipient_id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let message = insert_message(
+        &mut tx,
+        conversation_id,
+        user_id,
+        &body.body,
+        body.record_id,
+    )
+    .await
+    .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    notify_recipient(&data.db, &sender, recipient.user_id, conversation_id).await;
+
+    println!(
+        "POST: {} messaged {} in conversation {}",
+        sender.user_name, recipient.user_name, conversation_id
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "conversation_id": conversation_id,
+            "message": message,
+        })),
+    ))
+}
+
+/// GET the messages in a conversation, newest first
+pub async fn get_conversation_messages(
+    Path((user_id, conversation_id)): Path<(Uuid, Uuid)>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let other_user_id = find_other_participant(&data.db, conversation_id, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(20);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let messages = sqlx::query_as::<_, DirectMessageModel>(&format!(
+        "{} WHERE m.conversation_id = $1
+        ORDER BY m.created_at DESC LIMIT $2 OFFSET $3",
+        MESSAGE_QUERY
+    ))
+    .bind(conversation_id)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} messages in conversation {}",
+        messages.len(),
+        conversation_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "conversation_id": conversation_id,
+        "user_id": other_user_id,
+        "results": messages.len(),
+        "messages": messages,
+    })))
+}
+
+/// POST send a message in an existing conversation
+pub async fn send_message(
+    Path((user_id, conversation_id)): Path<(Uuid, Uuid)>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<SendMessageSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_message_body(&body.body)?;
+
+    let other_user_id = find_other_participant(&data.db, conversation_id, user_id).await?;
+    let sender = find_user(&data.db, user_id).await?;
+    let recipient = find_user(&data.db, other_user_id).await?;
+    check_can_message(&data.db, &sender, &recipient).await?;
+
+    if let Some(record_id) = body.record_id {
+        check_record_exists(&data.db, record_id).await?;
+    }
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let message = insert_message(
+        &mut tx,
+        conversation_id,
+        user_id,
+        &body.body,
+        body.record_id,
+    )
+    .await
+    .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    notify_recipient(&data.db, &sender, recipient.user_id, conversation_id).await;
+
+    println!(
+        "POST: {} messaged {} in conversation {}",
+        sender.user_name, recipient.user_name, conversation_id
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "message": message,
+        })),
+    ))
+}
+
+/// POST mark the messages a user received in a conversation as read
+pub async fn mark_conversation_read(
+    Path((user_id, conversation_id)): Path<(Uuid, Uuid)>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_other_participant(&data.db, conversation_id, user_id).await?;
+
+    let marked_read = sqlx::query!(
+        "UPDATE direct_messages SET read_at = NOW()
+        WHERE conversation_id = $1 AND sender_id <> $2 AND read_at IS NULL",
+        conversation_id,
+        user_id
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?
+    .rows_affected();
+
+    println!(
+        "POST: marked {} messages read in conversation {} for user_id: {}",
+        marked_read, conversation_id, user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "marked_read": marked_read,
+    })))
+}
+
+/// POST block a user. they stop following each other and neither can follow
+/// or message the other until the block is lifted
+pub async fn block_user(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<BlockUserSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if user_id == body.blocked_id {
+        let error_response = json!({
+            "status": "fail",
+            "message": "users can't block themselves"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let blocker = find_user(&data.db, user_id).await?;
+    let blocked = find_user(&data.db, body.blocked_id).await?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let insert_result = sqlx::query!(
+        "INSERT INTO user_blocks (blocker_id, blocked_id) VALUES ($1, $2)",
+        user_id,
+        body.blocked_id
+    )
+    .execute(&mut *tx)
+    .await;
+
+    match insert_result {
+        Ok(_) => {}
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("{} already blocked {}", blocker.user_name, blocked.user_name)
+            });
+            return Err((StatusCode::CONFLICT, Json(error_response)));
+        }
+        Err(e) => return Err(internal_error(e)),
+    }
+
+    sqlx::query!(
+        "DELETE FROM user_follows
+        WHERE (follower_id = $1 AND followed_id = $2) OR (follower_id = $2 AND followed_id = $1)",
+        user_id,
+        body.blocked_id
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    println!("POST: {} blocked {}", blocker.user_name, blocked.user_name);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "user_id": user_id,
+            "blocked_id": body.blocked_id,
+        })),
+    ))
+}
+
+/// DELETE lift a block. follows removed by the block aren't restored
+pub async fn unblock_user(
+    Path((user_id, blocked_id)): Path<(Uuid, Uuid)>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let delete_query = sqlx::query!(
+        "DELETE FROM user_blocks WHERE blocker_id = $1 AND blocked_id = $2",
+        user_id,
+        blocked_id
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?
+    .rows_affected();
+
+    if delete_query == 0 {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("user {} hasn't blocked user {}", user_id, blocked_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    }
+
+    println!("DELETE: user {} unblocked user {}", user_id, blocked_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET the users someone has blocked, most recent first
+pub async fn get_blocked_users(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_user(&data.db, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let blocked = sqlx::query_as!(
+        BlockModel,
+        "SELECT u.user_id, u.user_name, b.created_at AS blocked_at
+        FROM user_blocks b JOIN users u ON u.user_id = b.blocked_id
+        WHERE b.blocker_id = $1
+        ORDER BY b.created_at DESC LIMIT $2 OFFSET $3",
+        user_id,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!("GET: user {} has blocked {} users", user_id, blocked.len());
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": blocked.len(),
+        "blocked": blocked,
+    })))
+}
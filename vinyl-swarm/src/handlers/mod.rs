@@ -1,3 +1,34 @@
+pub mod activity;
+pub mod alerts;
+pub mod comments;
+pub mod events;
+pub mod genres;
+pub mod gifts;
+pub mod inventory;
+pub mod lists;
+pub mod loans;
+pub mod messages;
+pub mod moderation;
+pub mod notifications;
+pub mod plays;
+pub mod recommendations;
 pub mod record_stores;
 pub mod records;
+pub mod reviews;
+pub mod revisions;
+pub mod store_claims;
+pub mod store_hours;
+pub mod trips;
 pub mod users;
+
+use axum::{http::StatusCode, Json};
+use serde_json::json;
+
+/// internal_error:
+/// wraps an unexpected database error in the standard error response
+pub fn internal_error(err: sqlx::Error) -> (StatusCode, Json<serde_json::Value>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(json!({"status": "error", "message": format!("{:?}", err)})),
+    )
+}
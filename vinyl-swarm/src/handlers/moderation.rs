@@ -0,0 +1,795 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use sqlx::{PgConnection, Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    handlers::{
+        internal_error, record_stores::apply_record_store_edit, records::apply_record_edit,
+    },
+    models::{
+        moderation::{
+            ApproveSuggestionSchema, DuplicateFilterOptions, DuplicateRecordPair, FilterOptions,
+            MergeRecordsSchema, RejectSuggestionSchema, SuggestedEditModel,
+            SuggestionFilterOptions,
+        },
+        record::{RecordModel, UpdateRecordSchema},
+        store::UpdateRecordStoreSchema,
+        user::UserModel,
+    },
+    AppState,
+};
+
+/// find_moderator:
+/// looks up the acting user and ensures they are allowed to moderate the catalog.
+/// responds with NOT_FOUND for unknown users and FORBIDDEN for regular members.
+pub async fn find_moderator(
+    db: &Pool<Postgres>,
+    moderator_id: Uuid,
+) -> Result<UserModel, (StatusCode, Json<serde_json::Value>)> {
+    let user_query = sqlx::query_as!(
+        UserModel,
+        "SELECT * FROM users WHERE user_id = $1",
+        moderator_id
+    )
+    .fetch_optional(db)
+    .await;
+
+    match user_query {
+        Ok(Some(user)) if user.is_moderator() => Ok(user),
+        Ok(Some(user)) => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("user {} is not a moderator", user.user_name)
+            });
+            Err((StatusCode::FORBIDDEN, Json(error_response)))
+        }
+        Ok(None) => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("user_id {} not found", moderator_id)
+            });
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"status": "error", "message": format!("{:?}", e)})),
+        )),
+    }
+}
+
+/// GET likely duplicate records
+/// compares normalized artist and title strings using trigram similarity,
+/// only records sharing the same normalized format are considered duplicates.
+pub async fn find_duplicate_records(
+    Query(opts): Query<DuplicateFilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_moderator(&data.db, opts.moderator_id).await?;
+
+    let min_similarity = opts.min_similarity.unwrap_or(0.6);
+    let limit = opts.limit.unwrap_or(25);
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    // the % operator matches at the session's similarity threshold, set for this
+    // transaction only so pooled connections keep the default
+    sqlx::query!(
+        "SELECT set_config('pg_trgm.similarity_threshold', $1::REAL::TEXT, true)",
+        min_similarity
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    // % finds the candidates through the trigram indexes, the scores are only
+    // computed for the pairs it lets through
+    let pairs = sqlx::query_as!(
+        DuplicateRecordPair,
+        r#"SELECT record_id AS "record_id!", duplicate_record_id AS "duplicate_record_id!",
+            artist_similarity AS "artist_similarity!", title_similarity AS "title_similarity!"
+        FROM (
+            SELECT a.record_id, b.record_id AS duplicate_record_id,
+                similarity(normalize_catalog_text(a.artist), normalize_catalog_text(b.artist)) AS artist_similarity,
+                similarity(normalize_catalog_text(a.title), normalize_catalog_text(b.title)) AS title_similarity
+            FROM records a
+            JOIN records b ON normalize_catalog_text(b.artist) % normalize_catalog_text(a.artist)
+                AND normalize_catalog_text(b.title) % normalize_catalog_text(a.title)
+                AND a.record_id < b.record_id
+                AND normalize_catalog_text(a.format) = normalize_catalog_text(b.format)
+        ) pairs
+        ORDER BY artist_similarity + title_similarity DESC
+        LIMIT $1"#,
+        limit as i64
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    // load the records for every pair so moderators can compare them
+    let record_ids: Vec<Uuid> = pairs
+        .iter()
+        .flat_map(|pair| [pair.record_id, pair.duplicate_record_id])
+        .collect();
+
+    let records = sqlx::query_as!(
+        RecordModel,
+        "SELECT * FROM records WHERE record_id = ANY($1)",
+        &record_ids
+    )
+    .fetch_all(&data.db)
+    .await
+    .unwrap_or_default();
+
+    let find = |id: Uuid| records.iter().find(|record| record.record_id == id);
+
+    let duplicates: Vec<serde_json::Value> = pairs
+        .iter()
+        .map(|pair| {
+            json!({
+                "artist_similarity": pair.artist_similarity,
+                "title_similarity": pair.title_similarity,
+                "record": find(pair.record_id),
+                "duplicate": find(pair.duplicate_record_id),
+            })
+        })
+        .collect();
+
+    println!(
+        "GET: returning {} possible duplicate records",
+        duplicates.len()
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": duplicates.len(),
+        "duplicates": duplicates,
+    })))
+}
+
+/// POST merge duplicate records
+/// moves everything that references the duplicates over to the surviving record,
+/// then removes the duplicates, all in one transaction. refused with the loans
+/// listed while a user has more than one of the records lent out
+pub async fn merge_records(
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<MergeRecordsSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_moderator(&data.db, body.moderator_id).await?;
+
+    if body.duplicate_record_ids.is_empty()
+        || body
+            .duplicate_record_ids
+            .contains(&body.surviving_record_id)
+    {
+        let error_response = json!({
+            "status": "fail",
+            "message": "duplicate_record_ids must be supplied and cannot contain the surviving record"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    // every record taking part in the merge has to exist, and stays locked
+    // until the merge commits
+    let found_records = sqlx::query!(
+        "SELECT record_id FROM records WHERE record_id = $1 OR record_id = ANY($2) FOR UPDATE",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let mut missing_ids = vec![body.surviving_record_id];
+    missing_ids.extend(body.duplicate_record_ids.iter());
+    missing_ids.retain(|id| !found_records.iter().any(|row| row.record_id == *id));
+
+    if !missing_ids.is_empty() {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("record_ids not found: {:?}", missing_ids)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    }
+
+    // a collection only keeps one row per record, so a user with more than one of
+    // the merged records lent out has to get one back before the merge can happen
+    let conflicting_loans = sqlx::query!(
+        r#"SELECT loan_id AS "loan_id!", user_id AS "user_id!", record_id AS "record_id!",
+            borrower_name AS "borrower_name!", lent_date AS "lent_date!"
+        FROM (
+            SELECT l.loan_id, ur.user_id, ur.record_id, l.borrower_name, l.lent_date,
+                COUNT(*) OVER (PARTITION BY ur.user_id) AS open_loans
+            FROM record_loans l
+            JOIN user_records ur ON ur.user_record_id = l.user_record_id
+            WHERE (ur.record_id = $1 OR ur.record_id = ANY($2)) AND l.returned_date IS NULL
+        ) open
+        WHERE open_loans > 1
+        ORDER BY user_id, lent_date"#,
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    if !conflicting_loans.is_empty() {
+        let loans: Vec<serde_json::Value> = conflicting_loans
+            .iter()
+            .map(|loan| {
+                json!({
+                    "loan_id": loan.loan_id,
+                    "user_id": loan.user_id,
+                    "record_id": loan.record_id,
+                    "borrower_name": loan.borrower_name,
+                    "lent_date": loan.lent_date,
+                })
+            })
+            .collect();
+        let error_response = json!({
+            "status": "fail",
+            "message": "some users have more than one of these records lent out, one of each user's loans has to be returned first",
+            "conflicting_loans": loans,
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    let record = merge_into_surviving_record(&mut tx, &body)
+        .await
+        .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    println!(
+        "POST: merged {} duplicates into {} by {}",
+        body.duplicate_record_ids.len(),
+        record.title,
+        record.artist
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "merged": body.duplicate_record_ids.len(),
+        "record": record,
+    })))
+}
+
+/// merge_into_surviving_record:
+/// users that already hold the surviving record, or several of the duplicates,
+/// keep a single row so the unique constraints are never violated.
+/// runs on the caller's transaction, which commits or rolls back the whole merge
+async fn merge_into_surviving_record(
+    tx: &mut PgConnection,
+    body: &MergeRecordsSchema,
+) -> Result<RecordModel, sqlx::Error> {
+    // collections. plays and loans logged on a collection row that's about to go
+    // move to the row that's kept. merge_records already turned away merges that
+    // would leave a row with two loans still out
+    sqlx::query!(
+        "UPDATE record_plays p SET user_record_id = kept.user_record_id
+        FROM user_records gone
+        CROSS JOIN LATERAL (
+            SELECT k.user_record_id FROM user_records k
+            WHERE k.user_id = gone.user_id AND (k.record_id = $1 OR k.record_id = ANY($2))
+            ORDER BY k.record_id = $1 DESC, k.user_record_id LIMIT 1
+        ) kept
+        WHERE p.user_record_id = gone.user_record_id AND gone.record_id = ANY($2)
+        AND kept.user_record_id <> gone.user_record_id",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE record_loans l SET user_record_id = kept.user_record_id
+        FROM user_records gone
+        CROSS JOIN LATERAL (
+            SELECT k.user_record_id FROM user_records k
+            WHERE k.user_id = gone.user_id AND (k.record_id = $1 OR k.record_id = ANY($2))
+            ORDER BY k.record_id = $1 DESC, k.user_record_id LIMIT 1
+        ) kept
+        WHERE l.user_record_id = gone.user_record_id AND gone.record_id = ANY($2)
+        AND kept.user_record_id <> gone.user_record_id",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM user_records ur WHERE ur.record_id = ANY($2) AND EXISTS (
+            SELECT 1 FROM user_records other WHERE other.user_id = ur.user_id
+            AND (other.record_id = $1 OR (other.record_id = ANY($2) AND other.user_record_id < ur.user_record_id)))",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE user_records SET record_id = $1 WHERE record_id = ANY($2)",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // wishlists. a gift reserved on a wishlist row that's about to go moves to the
+    // row that's kept, unless that one is already reserved
+    sqlx::query!(
+        "UPDATE gift_reservations g SET user_wish_list_id = moved.kept_id
+        FROM (
+            SELECT DISTINCT ON (kept.user_wish_list_id) gr.reservation_id, kept.user_wish_list_id AS kept_id
+            FROM gift_reservations gr
+            JOIN user_wishlist gone ON gone.user_wish_list_id = gr.user_wish_list_id
+            CROSS JOIN LATERAL (
+                SELECT k.user_wish_list_id FROM user_wishlist k
+                WHERE k.user_id = gone.user_id AND (k.record_id = $1 OR k.record_id = ANY($2))
+                ORDER BY k.record_id = $1 DESC, k.user_wish_list_id LIMIT 1
+            ) kept
+            WHERE gone.record_id = ANY($2) AND kept.user_wish_list_id <> gone.user_wish_list_id
+            AND NOT EXISTS (SELECT 1 FROM gift_reservations r WHERE r.user_wish_list_id = kept.user_wish_list_id)
+            ORDER BY kept.user_wish_list_id, gr.created_at
+        ) moved
+        WHERE g.reservation_id = moved.reservation_id",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "DELETE FROM user_wishlist uw WHERE uw.record_id = ANY($2) AND EXISTS (
+            SELECT 1 FROM user_wishlist other WHERE other.user_id = uw.user_id
+            AND (other.record_id = $1 OR (other.record_id = ANY($2) AND other.user_wish_list_id < uw.user_wish_list_id)))",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE user_wishlist SET record_id = $1 WHERE record_id = ANY($2)",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // reviews, a user who reviewed more than one of the copies keeps the
+    // review of the surviving record, or else their first one. undated reviews
+    // count as the oldest and review_id settles ties
+    sqlx::query!(
+        "DELETE FROM record_reviews rr WHERE rr.record_id = ANY($2) AND EXISTS (
+            SELECT 1 FROM record_reviews other WHERE other.user_id = rr.user_id
+            AND (other.record_id = $1 OR (other.record_id = ANY($2)
+                AND (COALESCE(other.created_at, '-infinity'), other.review_id)
+                    < (COALESCE(rr.created_at, '-infinity'), rr.review_id))))",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE record_reviews SET record_id = $1 WHERE record_id = ANY($2)",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // store inventory, copies listed under a duplicate are added to the surviving
+    // record's listing in the same condition
+    sqlx::query!(
+        "INSERT INTO store_inventory (record_store_id, record_id, price, condition, quantity, reported_by)
+        SELECT record_store_id, $1, MIN(price), condition, SUM(quantity),
+            (array_agg(reported_by ORDER BY updated_at DESC))[1]
+        FROM store_inventory WHERE record_id = ANY($2)
+        GROUP BY record_store_id, condition
+        ON CONFLICT (record_store_id, record_id, condition) DO UPDATE
+        SET quantity = store_inventory.quantity + EXCLUDED.quantity, updated_at = NOW()",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // store events, the duplicates' own rows go when the records are deleted
+    sqlx::query!(
+        "INSERT INTO store_event_records (event_id, record_id)
+        SELECT event_id, $1 FROM store_event_records WHERE record_id = ANY($2)
+        ON CONFLICT DO NOTHING",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // activity about a duplicate now points at the surviving record
+    sqlx::query!(
+        "UPDATE activity_events SET record_id = $1 WHERE record_id = ANY($2)",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // lists keep the surviving record where it was, or else the duplicate placed
+    // highest, and the positions left behind are closed up
+    sqlx::query!(
+        "DELETE FROM record_list_items li WHERE li.record_id = ANY($2) AND EXISTS (
+            SELECT 1 FROM record_list_items other WHERE other.list_id = li.list_id
+            AND (other.record_id = $1 OR (other.record_id = ANY($2) AND other.position < li.position)))",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE record_list_items SET record_id = $1 WHERE record_id = ANY($2)",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE record_list_items li SET position = renumbered.position
+        FROM (
+            SELECT list_id, record_id, row_number() OVER (PARTITION BY list_id ORDER BY position) AS position
+            FROM record_list_items
+            WHERE list_id IN (SELECT list_id FROM record_list_items WHERE record_id = $1)
+        ) renumbered
+        WHERE li.list_id = renumbered.list_id AND li.record_id = renumbered.record_id
+        AND li.position <> renumbered.position",
+        body.surviving_record_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // records attached to direct messages
+    sqlx::query!(
+        "UPDATE direct_messages SET record_id = $1 WHERE record_id = ANY($2)",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // comment threads on the duplicates join the surviving record's
+    sqlx::query!(
+        "UPDATE comments SET record_id = $1 WHERE record_id = ANY($2)",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // suggestions for a duplicate, pending ones included, are about the surviving record now
+    sqlx::query!(
+        "UPDATE suggested_edits SET entity_id = $1 WHERE entity_type = 'record' AND entity_id = ANY($2)",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // revision histories are joined and renumbered oldest first. the numbers go
+    // negative on the way so they never collide with the ones being replaced
+    sqlx::query!(
+        "UPDATE catalog_revisions c SET entity_id = $1, revision_number = -joined.revision_number
+        FROM (
+            SELECT revision_id,
+                row_number() OVER (ORDER BY created_at, entity_id = $1 DESC, revision_number, revision_id) AS revision_number
+            FROM catalog_revisions
+            WHERE entity_type = 'record' AND (entity_id = $1 OR entity_id = ANY($2))
+        ) joined
+        WHERE c.revision_id = joined.revision_id",
+        body.surviving_record_id,
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        "UPDATE catalog_revisions SET revision_number = -revision_number
+        WHERE entity_type = 'record' AND entity_id = $1 AND revision_number < 0",
+        body.surviving_record_id
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    // nothing references the duplicates anymore
+    sqlx::query!(
+        "DELETE FROM records WHERE record_id = ANY($1)",
+        &body.duplicate_record_ids
+    )
+    .execute(&mut *tx)
+    .await?;
+
+    let record = sqlx::query_as!(
+        RecordModel,
+        "SELECT * FROM records WHERE record_id = $1",
+        body.surviving_record_id
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    Ok(record)
+}
+
+// SUGGESTED EDIT QUEUE:
+
+/// submit_suggestion:
+/// queues a PATCH body against an existing record or record store
+pub async fn submit_suggestion(
+    db: &Pool<Postgres>,
+    entity_type: &str,
+    entity_id: Uuid,
+    submitter_id: Option<Uuid>,
+    changes: serde_json::Value,
+) -> Result<SuggestedEditModel, (StatusCode, Json<serde_json::Value>)> {
+    let entity_check = match entity_type {
+        "record" => sqlx::query_scalar!(
+            "SELECT record_id FROM records WHERE record_id = $1",
+            entity_id
+        )
+        .fetch_optional(db)
+        .await
+        .map_err(internal_error)?,
+        _ => sqlx::query_scalar!(
+            "SELECT record_store_id FROM record_stores WHERE record_store_id = $1",
+            entity_id
+        )
+        .fetch_optional(db)
+        .await
+        .map_err(internal_error)?,
+    };
+
+    if entity_check.is_none() {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("{} id: {} not found", entity_type.replace('_', " "), entity_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    }
+
+    sqlx::query_as!(
+        SuggestedEditModel,
+        "INSERT INTO suggested_edits (entity_type, entity_id, submitter_id, changes)
+        VALUES ($1, $2, $3, $4) RETURNING *",
+        entity_type,
+        entity_id,
+        submitter_id,
+        changes
+    )
+    .fetch_one(db)
+    .await
+    .map_err(internal_error)
+}
+
+/// GET the suggested edit queue
+/// defaults to pending suggestions, oldest first so nothing waits forever
+pub async fn list_suggestions(
+    Query(opts): Query<SuggestionFilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_moderator(&data.db, opts.moderator_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let suggestions = sqlx::query_as!(
+        SuggestedEditModel,
+        "SELECT * FROM suggested_edits
+        WHERE suggestion_status = $1 AND ($2::TEXT IS NULL OR entity_type = $2)
+        ORDER BY created_at LIMIT $3 OFFSET $4",
+        opts.suggestion_status.as_deref().unwrap_or("pending"),
+        opts.entity_type,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!("GET: returning {} suggested edits", suggestions.len());
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": suggestions.len(),
+        "suggestions": suggestions,
+    })))
+}
+
+/// find_pending_suggestion:
+/// locks the suggestion so it can only be reviewed once
+async fn find_pending_suggestion(
+    conn: &mut PgConnection,
+    suggestion_id: Uuid,
+) -> Result<SuggestedEditModel, (StatusCode, Json<serde_json::Value>)> {
+    let suggestion = sqlx::query_as!(
+        SuggestedEditModel,
+        "SELECT * FROM suggested_edits WHERE suggestion_id = $1 FOR UPDATE",
+        suggestion_id
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(internal_error)?;
+
+    match suggestion {
+        Some(suggestion) if suggestion.suggestion_status == "pending" => Ok(suggestion),
+        Some(suggestion) => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("suggestion_id {} was already {}", suggestion_id, suggestion.suggestion_status)
+            });
+            Err((StatusCode::CONFLICT, Json(error_response)))
+        }
+        None => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("suggestion_id {} not found", suggestion_id)
+            });
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+    }
+}
+
+/// POST approve a suggested edit
+/// applies the change through the same code as a direct edit, credited to the submitter
+pub async fn approve_suggestion(
+    Path(id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<ApproveSuggestionSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_moderator(&data.db, body.moderator_id).await?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let suggestion = find_pending_suggestion(&mut tx, id).await?;
+
+    let invalid_changes = |e: serde_json::Error| {
+        (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(json!({"status": "fail", "message": format!("invalid suggested changes: {}", e)})),
+        )
+    };
+
+    let applied = match suggestion.entity_type.as_str() {
+        "record" => {
+            let mut changes: UpdateRecordSchema =
+                serde_json::from_value(suggestion.changes.clone()).map_err(invalid_changes)?;
+            changes.editor_id = suggestion.submitter_id;
+
+            json!(apply_record_edit(&mut tx, suggestion.entity_id, changes).await?)
+        }
+        _ => {
+            let mut changes: UpdateRecordStoreSchema =
+                serde_json::from_value(suggestion.changes.clone()).map_err(invalid_changes)?;
+            changes.editor_id = suggestion.submitter_id;
+
+            json!(apply_record_store_edit(&mut tx, suggestion.entity_id, changes).await?)
+        }
+    };
+
+    let reviewed_suggestion = sqlx::query_as!(
+        SuggestedEditModel,
+        "UPDATE suggested_edits SET suggestion_status = 'approved', reviewer_id = $1, reviewed_at = NOW()
+        WHERE suggestion_id = $2 RETURNING *",
+        body.moderator_id,
+        id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    println!(
+        "POST: approved suggested edit {} to {} {}",
+        id, reviewed_suggestion.entity_type, reviewed_suggestion.entity_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "suggestion": reviewed_suggestion,
+        reviewed_suggestion.entity_type.as_str(): applied,
+    })))
+}
+
+/// POST reject a suggested edit with a reason the submitter can read
+pub async fn reject_suggestion(
+    Path(id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<RejectSuggestionSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_moderator(&data.db, body.moderator_id).await?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    find_pending_suggestion(&mut tx, id).await?;
+
+    let reviewed_suggestion = sqlx::query_as!(
+        SuggestedEditModel,
+        "UPDATE suggested_edits SET suggestion_status = 'rejected', reviewer_id = $1,
+        rejection_reason = $2, reviewed_at = NOW()
+        WHERE suggestion_id = $3 RETURNING *",
+        body.moderator_id,
+        body.rejection_reason,
+        id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    println!("POST: rejected suggested edit {}", id);
+
+    Ok(Json(json!({
+        "status": "success",
+        "suggestion": reviewed_suggestion,
+    })))
+}
+
+/// GET the suggestions a user submitted and where they stand
+pub async fn get_user_suggestions(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let check_user_query = sqlx::query!("SELECT user_id FROM users WHERE user_id = $1", user_id)
+        .fetch_optional(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    if check_user_query.is_none() {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("user_id {} not found", user_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    }
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let suggestions = sqlx::query_as!(
+        SuggestedEditModel,
+        "SELECT * FROM suggested_edits WHERE submitter_id = $1
+        ORDER BY created_at DESC LIMIT $2 OFFSET $3",
+        user_id,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} suggested edits for user_id: {}",
+        suggestions.len(),
+        user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": suggestions.len(),
+        "suggestions": suggestions,
+    })))
+}
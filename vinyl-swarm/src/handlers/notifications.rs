@@ -0,0 +1,275 @@
+use std::{collections::HashMap, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    handlers::{internal_error, users::find_user},
+    models::notification::{
+        MarkNotificationsReadSchema, NotificationFilterOptions, NotificationModel,
+        NotificationPreferenceModel, NOTIFICATION_TYPES,
+    },
+    AppState,
+};
+
+/// notify:
+/// puts a notification in a user's inbox unless they've turned that type off.
+/// like activity logging, a notification that can't be sent never fails the
+/// change it's about
+pub async fn notify(
+    db: &Pool<Postgres>,
+    user_id: Uuid,
+    notification_type: &str,
+    message: &str,
+    actor_id: Option<Uuid>,
+    subject_id: Option<Uuid>,
+) {
+    let insert_result = sqlx::query!(
+        "INSERT INTO notifications (user_id, notification_type, message, actor_id, subject_id)
+        SELECT $1, $2::VARCHAR, $3::TEXT, $4::uuid, $5::uuid
+        WHERE NOT EXISTS (
+            SELECT 1 FROM notification_preferences
+            WHERE user_id = $1 AND notification_type = $2 AND NOT enabled
+        )",
+        user_id,
+        notification_type,
+        message,
+        actor_id,
+        subject_id
+    )
+    .execute(db)
+    .await;
+
+    if let Err(e) = insert_result {
+        println!(
+            "NOTIFY: couldn't send {} to user {}: {:?}",
+            notification_type, user_id, e
+        );
+    }
+}
+
+/// GET a user's notifications, newest first, along with how many are unread
+pub async fn get_user_notifications(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<NotificationFilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_user(&data.db, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let notifications = sqlx::query_as!(
+        NotificationModel,
+        r#"SELECT n.notification_id, n.notification_type, n.message, n.actor_id,
+            a.user_name AS "actor_name?", n.subject_id, n.created_at, n.read_at
+        FROM notifications n
+        LEFT JOIN users a ON a.user_id = n.actor_id
+        WHERE n.user_id = $1 AND ($2 IS NOT TRUE OR n.read_at IS NULL)
+        AND ($3::VARCHAR IS NULL OR n.notification_type = $3)
+        ORDER BY n.created_at DESC LIMIT $4 OFFSET $5"#,
+        user_id,
+        opts.unread_only,
+        opts.notification_type,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let unread = sqlx::query_scalar!(
+        r#"SELECT COUNT(*) AS "unread!" FROM notifications WHERE user_id = $1 AND read_at IS NULL"#,
+        user_id
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} notifications for user_id: {}",
+        notifications.len(),
+        user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": notifications.len(),
+        "unread": unread,
+        "notifications": notifications,
+    })))
+}
+
+/// POST mark some or all of a user's notifications as read
+pub async fn mark_notifications_read(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<MarkNotificationsReadSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_user(&data.db, user_id).await?;
+
+    let marked_read = sqlx::query!(
+        "UPDATE notifications SET read_at = NOW()
+        WHERE user_id = $1 AND read_at IS NULL
+        AND ($2::uuid[] IS NULL OR notification_id = ANY($2))",
+        user_id,
+        body.notification_ids.as_deref()
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?
+    .rows_affected();
+
+    println!(
+        "POST: marked {} notifications read for user_id: {}",
+        marked_read, user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "marked_read": marked_read,
+    })))
+}
+
+/// POST mark one notification as read
+pub async fn mark_notification_read(
+    Path((user_id, notification_id)): Path<(Uuid, Uuid)>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let notification = sqlx::query_as!(
+        NotificationModel,
+        r#"WITH marked AS (
+            UPDATE notifications SET read_at = COALESCE(read_at, NOW())
+            WHERE notification_id = $1 AND user_id = $2
+            RETURNING *
+        )
+        SELECT n.notification_id, n.notification_type, n.message, n.actor_id,
+            a.user_name AS "actor_name?", n.subject_id, n.created_at, n.read_at
+        FROM marked n
+        LEFT JOIN users a ON a.user_id = n.actor_id"#,
+        notification_id,
+        user_id
+    )
+    .fetch_optional(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(notification) = notification else {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("notification_id {} not found for user {}", notification_id, user_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    println!(
+        "POST: marked notification {} read for user_id: {}",
+        notification_id, user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "notification": notification,
+    })))
+}
+
+/// fetch_notification_preferences:
+/// every notification type and whether the user gets it
+async fn fetch_notification_preferences(
+    db: &Pool<Postgres>,
+    user_id: Uuid,
+) -> Result<Vec<NotificationPreferenceModel>, sqlx::Error> {
+    sqlx::query_as!(
+        NotificationPreferenceModel,
+        r#"SELECT t.notification_type AS "notification_type!",
+            COALESCE(p.enabled, TRUE) AS "enabled!"
+        FROM unnest($2::VARCHAR[]) WITH ORDINALITY AS t (notification_type, position)
+        LEFT JOIN notification_preferences p
+            ON p.user_id = $1 AND p.notification_type = t.notification_type
+        ORDER BY t.position"#,
+        user_id,
+        &NOTIFICATION_TYPES.map(String::from)
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// GET which types of notification a user gets
+pub async fn get_notification_preferences(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_user(&data.db, user_id).await?;
+
+    let preferences = fetch_notification_preferences(&data.db, user_id)
+        .await
+        .map_err(internal_error)?;
+
+    println!("GET: notification preferences for user_id: {}", user_id);
+
+    Ok(Json(json!({
+        "status": "success",
+        "preferences": preferences,
+    })))
+}
+
+/// PATCH turn types of notification on or off, e.g. {"new_follower": false}.
+/// types left out of the body keep their setting
+pub async fn edit_notification_preferences(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<HashMap<String, bool>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if let Some(notification_type) = body
+        .keys()
+        .find(|notification_type| !NOTIFICATION_TYPES.contains(&notification_type.as_str()))
+    {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!(
+                "unknown notification type {}, must be one of {}",
+                notification_type,
+                NOTIFICATION_TYPES.join(", ")
+            )
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    find_user(&data.db, user_id).await?;
+
+    let (notification_types, enabled): (Vec<String>, Vec<bool>) = body.into_iter().unzip();
+
+    sqlx::query!(
+        "INSERT INTO notification_preferences (user_id, notification_type, enabled)
+        SELECT $1, * FROM unnest($2::VARCHAR[], $3::BOOLEAN[])
+        ON CONFLICT (user_id, notification_type) DO UPDATE SET enabled = EXCLUDED.enabled",
+        user_id,
+        &notification_types,
+        &enabled
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let preferences = fetch_notification_preferences(&data.db, user_id)
+        .await
+        .map_err(internal_error)?;
+
+    println!(
+        "PATCH: updated {} notification preferences for user_id: {}",
+        notification_types.len(),
+        user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "preferences": preferences,
+    })))
+}
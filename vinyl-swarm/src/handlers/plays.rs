@@ -0,0 +1,319 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::Utc;
+use serde_json::json;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+use crate::{
+    handlers::{internal_error, users::find_user},
+    models::play::{
+        FilterOptions, ListeningTimeModel, LogPlaySchema, PlayCountModel, PlayCountOptions,
+        PlayModel, NEGLECTED_AFTER_DAYS, SIDE_MAX_CHARS,
+    },
+    AppState,
+};
+
+/// plays as read into PlayModel, callers add their own WHERE clause
+const PLAY_QUERY: &str = "SELECT p.play_id, p.played_at, p.side, p.notes, to_jsonb(r) AS record
+    FROM record_plays p
+    JOIN user_records ur ON ur.user_record_id = p.user_record_id
+    JOIN records r ON r.record_id = ur.record_id";
+
+/// collection items as read into PlayCountModel, callers add their own
+/// HAVING and ORDER BY clauses
+const PLAY_COUNT_QUERY: &str = "SELECT COUNT(p.play_id) AS play_count,
+        MAX(p.played_at) AS last_played_at, ur.added_at, to_jsonb(r) AS record
+    FROM user_records ur
+    JOIN records r ON r.record_id = ur.record_id
+    LEFT JOIN record_plays p ON p.user_record_id = ur.user_record_id
+    WHERE ur.user_id = $1
+    GROUP BY ur.user_record_id, r.record_id";
+
+/// find_collection_item:
+/// the user_records row for a record in a user's collection,
+/// NOT_FOUND when they don't own the record
+pub async fn find_collection_item<'e>(
+    executor: impl PgExecutor<'e>,
+    user_id: Uuid,
+    record_id: Uuid,
+) -> Result<Uuid, (StatusCode, Json<serde_json::Value>)> {
+    let user_record_id = sqlx::query_scalar!(
+        "SELECT user_record_id FROM user_records WHERE user_id = $1 AND record_id = $2",
+        user_id,
+        record_id
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(internal_error)?;
+
+    user_record_id.ok_or_else(|| {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("record_id {} isn't in the collection of user {}", record_id, user_id)
+        });
+        (StatusCode::NOT_FOUND, Json(error_response))
+    })
+}
+
+/// GET a user's listening log, most recent plays first
+pub async fn get_user_plays(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = find_user(&data.db, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let plays = sqlx::query_as::<_, PlayModel>(&format!(
+        "{} WHERE ur.user_id = $1 ORDER BY p.played_at DESC LIMIT $2 OFFSET $3",
+        PLAY_QUERY
+    ))
+    .bind(user_id)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} plays for {}",
+        plays.len(),
+        user.user_name
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": plays.len(),
+        "plays": plays,
+    })))
+}
+
+/// POST log a play of a record in the user's collection
+pub async fn log_play(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<LogPlaySchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if body
+        .played_at
+        .is_some_and(|played_at| played_at > Utc::now())
+    {
+        let error_response = json!({
+            "status": "fail",
+            "message": "played_at can't be in the future"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let side = body.side.as_deref().map(str::trim);
+    if side.is_some_and(|side| side.is_empty() || side.chars().count() > SIDE_MAX_CHARS) {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("side has to be between 1 and {} characters", SIDE_MAX_CHARS)
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let user = find_user(&data.db, user_id).await?;
+    let user_record_id = find_collection_item(&data.db, user_id, body.record_id).await?;
+
+    let play_id = sqlx::query_scalar!(
+        "INSERT INTO record_plays (user_record_id, played_at, side, notes)
+        VALUES ($1, COALESCE($2, NOW()), $3, $4) RETURNING play_id",
+        user_record_id,
+        body.played_at,
+        side,
+        body.notes
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let play = sqlx::query_as::<_, PlayModel>(&format!("{} WHERE p.play_id = $1", PLAY_QUERY))
+        .bind(play_id)
+        .fetch_one(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    println!(
+        "POST: {} played {} by {}",
+        user.user_name, play.record.title, play.record.artist
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "play": play,
+        })),
+    ))
+}
+
+/// DELETE a play logged by mistake
+pub async fn delete_play(
+    Path((user_id, play_id)): Path<(Uuid, Uuid)>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let delete_query = sqlx::query!(
+        "DELETE FROM record_plays p USING user_records ur
+        WHERE p.user_record_id = ur.user_record_id AND ur.user_id = $1 AND p.play_id = $2",
+        user_id,
+        play_id
+    )
+    .execute(&data.db)
+    .await
+    .map_err(internal_error)?
+    .rows_affected();
+
+    if delete_query == 0 {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("play_id {} not found for user {}", play_id, user_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    }
+
+    println!("DELETE: play {} for user {}", play_id, user_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET a user's collection ranked by play count, most played first by default.
+/// ties go to whichever was played least recently when ranking by least played,
+/// and most recently otherwise
+pub async fn get_play_counts(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<PlayCountOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let order_by = match opts.order.as_deref().unwrap_or("most") {
+        "most" => "play_count DESC, last_played_at DESC NULLS LAST",
+        "least" => "play_count, last_played_at NULLS FIRST",
+        _ => {
+            let error_response = json!({
+                "status": "fail",
+                "message": "order must be most or least"
+            });
+            return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+        }
+    };
+
+    let user = find_user(&data.db, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let records = sqlx::query_as::<_, PlayCountModel>(&format!(
+        "{} ORDER BY {}, r.artist, r.title LIMIT $2 OFFSET $3",
+        PLAY_COUNT_QUERY, order_by
+    ))
+    .bind(user_id)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning play counts for {} records in {}'s collection",
+        records.len(),
+        user.user_name
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": records.len(),
+        "records": records,
+    })))
+}
+
+/// GET the records in a user's collection that haven't been played in
+/// NEGLECTED_AFTER_DAYS, never played records included, longest neglected first
+pub async fn get_neglected_records(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = find_user(&data.db, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let records = sqlx::query_as::<_, PlayCountModel>(&format!(
+        "{} HAVING MAX(p.played_at) IS NULL OR MAX(p.played_at) < NOW() - make_interval(days => $4)
+        ORDER BY last_played_at NULLS FIRST, ur.added_at, r.artist LIMIT $2 OFFSET $3",
+        PLAY_COUNT_QUERY
+    ))
+    .bind(user_id)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .bind(NEGLECTED_AFTER_DAYS)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} records {} hasn't played in a year",
+        records.len(),
+        user.user_name
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": records.len(),
+        "records": records,
+    })))
+}
+
+/// GET how long a user has spent listening, overall and over the last 30 days.
+/// an estimate whenever side plays are involved, see ListeningTimeModel
+pub async fn get_listening_time(
+    Path(user_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = find_user(&data.db, user_id).await?;
+
+    let totals = sqlx::query!(
+        r#"WITH play_seconds AS (
+            SELECT p.played_at,
+                EXTRACT(EPOCH FROM r.duration_length) * CASE WHEN p.side IS NULL THEN 1 ELSE 0.5 END AS seconds,
+                p.side IS NOT NULL OR r.duration_length IS NULL AS estimated
+            FROM record_plays p
+            JOIN user_records ur ON ur.user_record_id = p.user_record_id
+            JOIN records r ON r.record_id = ur.record_id
+            WHERE ur.user_id = $1
+        )
+        SELECT COUNT(*) AS "total_plays!",
+            COALESCE(ROUND(SUM(seconds)), 0)::BIGINT AS "listening_seconds!",
+            COUNT(*) FILTER (WHERE played_at >= NOW() - INTERVAL '30 days') AS "plays_last_30_days!",
+            COALESCE(ROUND(SUM(seconds) FILTER (WHERE played_at >= NOW() - INTERVAL '30 days')), 0)::BIGINT
+                AS "listening_seconds_last_30_days!",
+            COALESCE(bool_or(estimated), FALSE) AS "estimated!"
+        FROM play_seconds"#,
+        user_id
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!("GET: listening time for {}", user.user_name);
+
+    Ok(Json(json!({
+        "status": "success",
+        "listening_time": ListeningTimeModel {
+            total_plays: totals.total_plays,
+            listening_seconds: totals.listening_seconds,
+            plays_last_30_days: totals.plays_last_30_days,
+            listening_seconds_last_30_days: totals.listening_seconds_last_30_days,
+            estimated: totals.estimated,
+        },
+    })))
+}
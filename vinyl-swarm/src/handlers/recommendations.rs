@@ -0,0 +1,193 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    handlers::{internal_error, store_claims::find_admin, users::find_user},
+    models::recommendation::{FilterOptions, RecommendationModel, RefreshSimilaritySchema},
+    AppState,
+};
+
+/// how many of its most similar records are kept for each record
+const SIMILAR_RECORDS_KEPT: i64 = 50;
+
+/// how often the similarity job rebuilds record_similarity
+const SIMILARITY_REFRESH_MINUTES: u64 = 60;
+
+/// refresh_record_similarity:
+/// rebuilds the item-to-item similarity table. a record's collectors are the users
+/// who own it or have it on their wishlist, and two records are as similar as the
+/// cosine of their collectors: how many users have both, over the square root of
+/// the product of each record's collector count
+pub async fn refresh_record_similarity(db: &Pool<Postgres>) -> Result<u64, sqlx::Error> {
+    let mut tx = db.begin().await?;
+
+    sqlx::query!("DELETE FROM record_similarity")
+        .execute(&mut *tx)
+        .await?;
+
+    let pairs = sqlx::query!(
+        "WITH collectors AS (
+            SELECT user_id, record_id FROM user_records
+            UNION
+            SELECT user_id, record_id FROM user_wishlist
+        ),
+        collector_counts AS (
+            SELECT record_id, COUNT(*) AS collector_count FROM collectors GROUP BY record_id
+        ),
+        pairs AS (
+            SELECT a.record_id, b.record_id AS similar_record_id, COUNT(*) AS co_collectors
+            FROM collectors a
+            JOIN collectors b ON b.user_id = a.user_id AND b.record_id <> a.record_id
+            GROUP BY a.record_id, b.record_id
+        ),
+        scored AS (
+            SELECT p.record_id, p.similar_record_id, p.co_collectors,
+                p.co_collectors / sqrt(ca.collector_count * cb.collector_count) AS score
+            FROM pairs p
+            JOIN collector_counts ca ON ca.record_id = p.record_id
+            JOIN collector_counts cb ON cb.record_id = p.similar_record_id
+        ),
+        ranked AS (
+            SELECT *, row_number() OVER (
+                PARTITION BY record_id ORDER BY score DESC, co_collectors DESC
+            ) AS neighbour_rank
+            FROM scored
+        )
+        INSERT INTO record_similarity (record_id, similar_record_id, score, co_collectors)
+        SELECT record_id, similar_record_id, score, co_collectors
+        FROM ranked WHERE neighbour_rank <= $1",
+        SIMILAR_RECORDS_KEPT
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    tx.commit().await?;
+
+    Ok(pairs)
+}
+
+/// similarity_job:
+/// keeps record_similarity fresh, rebuilding it at startup and then every
+/// SIMILARITY_REFRESH_MINUTES
+pub async fn similarity_job(db: Pool<Postgres>) {
+    let mut interval = tokio::time::interval(Duration::from_secs(SIMILARITY_REFRESH_MINUTES * 60));
+
+    loop {
+        interval.tick().await;
+
+        match refresh_record_similarity(&db).await {
+            Ok(pairs) => println!("JOB: rebuilt record similarity, {} pairs", pairs),
+            Err(e) => println!("JOB: couldn't rebuild record similarity: {:?}", e),
+        }
+    }
+}
+
+/// POST rebuild record similarity now instead of waiting for the job
+pub async fn refresh_recommendations(
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<RefreshSimilaritySchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_admin(&data.db, body.admin_id).await?;
+
+    let pairs = refresh_record_similarity(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    println!("POST: rebuilt record similarity, {} pairs", pairs);
+
+    Ok(Json(json!({
+        "status": "success",
+        "pairs": pairs,
+    })))
+}
+
+/// GET records a user might like, leaving out the ones they own or wish for.
+/// records that collectors of the user's records also have come first, scored by
+/// their summed similarity. once those run out records sharing the user's genres
+/// fill in, scored by the share of the user's records in those genres, which covers
+/// users and records nobody else has collected yet
+pub async fn get_user_recommendations(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_user(&data.db, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let recommendations = sqlx::query_as::<_, RecommendationModel>(
+        "WITH seeds AS (
+            SELECT record_id FROM user_records WHERE user_id = $1
+            UNION
+            SELECT record_id FROM user_wishlist WHERE user_id = $1
+        ),
+        collaborative AS (
+            SELECT s.similar_record_id AS record_id, SUM(s.score) AS score,
+                array_agg(s.record_id ORDER BY s.score DESC) AS because_of
+            FROM record_similarity s JOIN seeds USING (record_id)
+            WHERE s.similar_record_id NOT IN (SELECT record_id FROM seeds)
+            GROUP BY s.similar_record_id
+        ),
+        seed_genres AS (
+            SELECT g.genre, COUNT(*) AS seed_count
+            FROM records r JOIN seeds USING (record_id)
+            CROSS JOIN unnest(r.genre) AS g (genre)
+            GROUP BY g.genre
+        ),
+        by_genre AS (
+            SELECT r.record_id,
+                SUM(sg.seed_count)::DOUBLE PRECISION / (SELECT SUM(seed_count) FROM seed_genres) AS score
+            FROM records r
+            CROSS JOIN unnest(r.genre) AS g (genre)
+            JOIN seed_genres sg ON sg.genre = g.genre
+            WHERE r.record_id NOT IN (SELECT record_id FROM seeds)
+            AND r.record_id NOT IN (SELECT record_id FROM collaborative)
+            GROUP BY r.record_id
+        ),
+        ranked AS (
+            SELECT record_id, 'collaborative' AS source, score, because_of, 0 AS tier
+            FROM collaborative
+            UNION ALL
+            SELECT b.record_id, 'genre', b.score,
+                ARRAY(
+                    SELECT s.record_id FROM seeds s JOIN records sr USING (record_id)
+                    WHERE sr.genre && r.genre ORDER BY sr.artist, sr.title
+                ),
+                1
+            FROM by_genre b JOIN records r USING (record_id)
+        )
+        SELECT ranked.source, ranked.score, ranked.because_of, to_jsonb(r) AS record
+        FROM ranked JOIN records r USING (record_id)
+        ORDER BY ranked.tier, ranked.score DESC, r.review_count DESC, r.artist, r.title
+        LIMIT $2 OFFSET $3",
+    )
+    .bind(user_id)
+    .bind(limit as i64)
+    .bind(offset as i64)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} recommendations for user_id: {}",
+        recommendations.len(),
+        user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": recommendations.len(),
+        "recommendations": recommendations,
+    })))
+}
@@ -9,19 +9,60 @@ use axum::{
 
 use crate::AppState;
 use crate::{
+    geocoder::geocode_zip,
+    handlers::{
+        activity::record_activity,
+        internal_error,
+        moderation::submit_suggestion,
+        revisions::{find_editor, save_revision},
+        store_claims::store_owner_ids,
+        store_hours::{
+            check_schedule, check_time_zone, fetch_hour_exceptions, fetch_opening_hours,
+            record_store_snapshot, replace_hour_exceptions, replace_opening_hours,
+            DEFAULT_TIME_ZONE,
+        },
+    },
     models::store::{
-        CreateRecordStoreSchema, FilterOptions, PatchRecordStoreSchema, PutRecordStoreSchema,
-        RecordStoreModel, UpdateRecordStoreSchema,
+        CreateRecordStoreSchema, FilterOptions, NearbyOptions, NearbyRecordStoreModel,
+        PatchRecordStoreSchema, PutRecordStoreSchema, RecordStoreModel, UpdateRecordStoreSchema,
     },
     models::user::UserModel,
 };
 use serde_json::json;
+use sqlx::{PgConnection, Pool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
+/// check_store_exists:
+/// 404 for an unknown record_store_id
+pub async fn check_store_exists(
+    db: &Pool<Postgres>,
+    record_store_id: Uuid,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let store_query = sqlx::query_scalar!(
+        "SELECT record_store_id FROM record_stores WHERE record_store_id = $1",
+        record_store_id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(internal_error)?;
+
+    if store_query.is_none() {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("record_store_id {} not found", record_store_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    }
+
+    Ok(())
+}
+
 /// GET all record stores from the database
 /// returns all record_stores
 /// params include the FilterOptions Struct to allow for pagination,
 /// this will return 10 if there is no chosen option query parameter.
+/// open_now=true leaves out stores that are closed right now,
+/// sort=rating lists the best reviewed stores first
 pub async fn list_all_stores(
     Query(opts): Query<FilterOptions>,
     State(data): State<Arc<AppState>>,
@@ -29,12 +70,29 @@ pub async fn list_all_stores(
     let limit = opts.limit.unwrap_or(10);
     let offset = (opts.page.unwrap_or(1) - 1) * limit;
 
-    // query as the record model and return all the records
+    let sort = opts.sort.as_deref().unwrap_or("name");
+    if !matches!(sort, "name" | "rating") {
+        let error_response = serde_json::json!({
+            "status": "fail",
+            "message": "sort must be either 'name' or 'rating'",
+        });
+        return (StatusCode::BAD_REQUEST, Json(error_response));
+    }
+
+    // query as the record model and return all the records,
+    // unreviewed stores go after the rated ones
     let query_result = sqlx::query_as!(
         RecordStoreModel,
-        "SELECT * FROM record_stores ORDER BY store_name LIMIT $1 OFFSET $2",
+        "SELECT * FROM record_stores
+        WHERE $3::bool IS NOT TRUE OR store_is_open(record_store_id, NOW())
+        ORDER BY CASE WHEN $4 = 'rating' THEN average_rating END DESC NULLS LAST,
+            CASE WHEN $4 = 'rating' THEN review_count END DESC,
+            store_name
+        LIMIT $1 OFFSET $2",
         limit as i32,
-        offset as i32
+        offset as i32,
+        opts.open_now,
+        sort
     )
     .fetch_all(&data.db)
     .await;
@@ -61,41 +119,89 @@ pub async fn list_all_stores(
     }
 }
 
+/// a new store's name has to be at least this similar to the name of a store
+/// at the same address for the two to count as the same shop
+const STORE_NAME_SIMILARITY: f32 = 0.5;
+
+/// find_matching_store
+/// an existing store at the same normalized street address, in the same zip code
+/// (or city and state when the zip codes differ), with a similar name.
+/// "1806 Frankfort Avenue, Suite 2" matches "1806 Frankfort Ave"
+pub async fn find_matching_store(
+    db: &Pool<Postgres>,
+    body: &CreateRecordStoreSchema,
+) -> Result<Option<RecordStoreModel>, sqlx::Error> {
+    sqlx::query_as!(
+        RecordStoreModel,
+        "SELECT * FROM record_stores
+        WHERE normalize_store_address(store_address) = normalize_store_address($1)
+        AND (normalize_store_zip(store_zip) = normalize_store_zip($2)
+            OR (lower(store_city) = lower($3) AND lower(store_state) = lower($4)))
+        AND similarity(normalize_catalog_text(store_name), normalize_catalog_text($5)) >= $6
+        ORDER BY similarity(normalize_catalog_text(store_name), normalize_catalog_text($5)) DESC
+        LIMIT 1",
+        body.store_address,
+        body.store_zip,
+        body.store_city,
+        body.store_state,
+        body.store_name,
+        STORE_NAME_SIMILARITY
+    )
+    .fetch_optional(db)
+    .await
+}
+
 /// create_record_store
 /// this handler is used for adding additional record stores that are worth shopping at.
-/// POST methods are recommended for this handler
+/// POST methods are recommended for this handler.
+/// a store that's already listed, even under a differently written address,
+/// is returned instead of creating a duplicate
 pub async fn create_record_store(
     State(data): State<Arc<AppState>>,
     Json(body): Json<CreateRecordStoreSchema>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_coordinates(&body)?;
+
     // check for an existing record_store
-    if let Ok(Some(found_store)) = sqlx::query_as!(
-        RecordStoreModel,
-        "SELECT * FROM record_stores WHERE store_name = $1 AND store_address = $2 AND store_city = $3 AND store_state = $4",
-        body.store_name,
-        body.store_address,
-        body.store_city,
-        body.store_state
-    ).fetch_optional(&data.db).await {
-        return Err((
-            StatusCode::CONFLICT,
-            Json(json!({"status": "fail", "message": format!("Record store '{}' already exists.", found_store.store_name)}))
-        ));
+    let matching_store = find_matching_store(&data.db, &body)
+        .await
+        .map_err(internal_error)?;
 
+    if let Some(found_store) = matching_store {
+        println!(
+            "POST: matched existing record store {}",
+            found_store.store_name
+        );
+
+        return Ok((
+            StatusCode::OK,
+            Json(json!({
+                "status": "success",
+                "message": format!("record store '{}' already exists", found_store.store_name),
+                "record_store": found_store
+            })),
+        ));
     }
 
+    let (latitude, longitude) = store_coordinates(&body);
+    let time_zone = body.time_zone.as_deref().unwrap_or(DEFAULT_TIME_ZONE);
+    check_time_zone(&data.db, time_zone).await?;
+
     // create the insert statement to add another record store
     let query_result = sqlx::query_as!(
         RecordStoreModel,
-        "INSERT INTO record_stores (store_name, store_address, store_city, store_state, store_zip, phone_number, website)
-        VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
+        "INSERT INTO record_stores (store_name, store_address, store_city, store_state, store_zip, phone_number, website, latitude, longitude, time_zone)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *",
         body.store_name.to_string(),
         body.store_address.to_string(),
         body.store_city.to_string(),
         body.store_state.to_string(),
         body.store_zip.to_string(),
         body.phone_number.to_owned().unwrap_or("".to_string()),
-        body.website.to_owned().unwrap_or("".to_string())
+        body.website.to_owned().unwrap_or("".to_string()),
+        latitude,
+        longitude,
+        time_zone
     ).fetch_one(&data.db)
     .await;
 
@@ -139,10 +245,25 @@ pub async fn find_record_store(
     // match for error from the Result
     match query_result {
         Ok(record_store) => {
+            let opening_hours = fetch_opening_hours(&data.db, id)
+                .await
+                .map_err(internal_error)?;
+            let hour_exceptions = fetch_hour_exceptions(&data.db, id, true)
+                .await
+                .map_err(internal_error)?;
+            let open_now =
+                sqlx::query_scalar!(r#"SELECT store_is_open($1, NOW()) AS "open_now!""#, id)
+                    .fetch_one(&data.db)
+                    .await
+                    .map_err(internal_error)?;
+
             let record_store_resp = serde_json::json!(
             {
                 "status": "success",
-                "record_store": record_store
+                "record_store": record_store,
+                "open_now": open_now,
+                "opening_hours": opening_hours,
+                "hour_exceptions": hour_exceptions,
             });
 
             println!("GET: returning {} record store", record_store.store_name);
@@ -161,17 +282,154 @@ pub async fn find_record_store(
     }
 }
 
+/// GET record stores within a radius (in miles) of a point, closest first
+/// stores without coordinates are left out
+pub async fn find_nearby_stores(
+    Query(opts): Query<NearbyOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let radius = opts.radius.unwrap_or(25.0);
+    let limit = opts.limit.unwrap_or(10);
+
+    if !(-90.0..=90.0).contains(&opts.lat) || !(-180.0..=180.0).contains(&opts.lon) || radius <= 0.0
+    {
+        let error_response = json!({
+            "status": "fail",
+            "message": "lat must be within -90 and 90, lon within -180 and 180 and radius above 0"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    // haversine distance on a sphere with the earth's mean radius in miles,
+    // LEAST keeps rounding from pushing asin's argument past 1 for antipodal points
+    let nearby_stores = sqlx::query_as::<_, NearbyRecordStoreModel>(
+        "SELECT * FROM (
+            SELECT *, 3958.8 * 2 * asin(LEAST(1, sqrt(
+                power(sin(radians(latitude - $1) / 2), 2)
+                + cos(radians($1)) * cos(radians(latitude)) * power(sin(radians(longitude - $2) / 2), 2)
+            ))) AS distance_miles
+            FROM record_stores
+            WHERE latitude IS NOT NULL AND longitude IS NOT NULL
+        ) AS distances
+        WHERE distance_miles <= $3
+        ORDER BY distance_miles
+        LIMIT $4",
+    )
+    .bind(opts.lat)
+    .bind(opts.lon)
+    .bind(radius)
+    .bind(limit as i64)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} record stores within {} miles",
+        nearby_stores.len(),
+        radius
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": nearby_stores.len(),
+        "record_stores": nearby_stores,
+    })))
+}
+
+/// edit_record_store
+/// edits from trusted users are applied right away, everyone else's
+/// land in the suggested edit queue for a moderator to approve.
+/// once a store has verified owners only their edits and moderators' are
+/// applied right away, trusted users go through the queue like everyone else
 pub async fn edit_record_store(
     Path(id): Path<Uuid>,
     State(data): State<Arc<AppState>>,
     Json(body): Json<UpdateRecordStoreSchema>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let editor = find_editor(&data.db, body.editor_id).await?;
+    let owner_ids = store_owner_ids(&data.db, id)
+        .await
+        .map_err(internal_error)?;
+
+    let edits_directly = editor.as_ref().is_some_and(|editor| {
+        editor.is_moderator()
+            || owner_ids.contains(&editor.user_id)
+            || (owner_ids.is_empty() && editor.is_trusted())
+    });
+
+    if !edits_directly {
+        let suggestion =
+            submit_suggestion(&data.db, "record_store", id, body.editor_id, json!(body)).await?;
+
+        println!("PATCH: queued edit to record_store_id {} for review", id);
+
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(json!({
+                "status": "success",
+                "message": "edit submitted for moderator review",
+                "suggestion": suggestion
+            })),
+        ));
+    }
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let record_store = apply_record_store_edit(&mut tx, id, body).await?;
+    tx.commit().await.map_err(internal_error)?;
+
+    let record_store_response = serde_json::json!(
+    {
+        "status": "success",
+        "record_store": record_store
+    });
+
+    println!("PATCH: editing {} store details", record_store.store_name);
+
+    Ok((StatusCode::OK, Json(record_store_response)))
+}
+
+/// check_coordinates
+/// BAD_REQUEST when a new store has only one of latitude and longitude
+fn check_coordinates(
+    body: &CreateRecordStoreSchema,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if body.latitude.is_some() != body.longitude.is_some() {
+        let error_response = json!({
+            "status": "fail",
+            "message": "latitude and longitude have to be supplied together"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    Ok(())
+}
+
+/// store_coordinates
+/// the coordinates supplied with a new store, otherwise the centroid of its zip code
+fn store_coordinates(body: &CreateRecordStoreSchema) -> (Option<f64>, Option<f64>) {
+    match (body.latitude, body.longitude) {
+        (Some(latitude), Some(longitude)) => (Some(latitude), Some(longitude)),
+        _ => geocode_zip(&body.store_zip).unzip(),
+    }
+}
+
+/// apply_record_store_edit
+/// updates only the store details present in the patch, null clears phone_number,
+/// website and the coordinates. opening hours and exceptions are replaced as a whole.
+/// keeps the change in the store's edit history.
+/// shared by edit_record_store and approved suggestions.
+pub async fn apply_record_store_edit(
+    conn: &mut PgConnection,
+    id: Uuid,
+    mut body: UpdateRecordStoreSchema,
+) -> Result<RecordStoreModel, (StatusCode, Json<serde_json::Value>)> {
+    // lock the row so revisions are numbered in the order edits land
     let query_result = sqlx::query_as!(
         RecordStoreModel,
-        "SELECT * FROM record_stores WHERE record_store_id = $1",
+        "SELECT * FROM record_stores WHERE record_store_id = $1 FOR UPDATE",
         id
     )
-    .fetch_one(&data.db)
+    .fetch_one(&mut *conn)
     .await;
 
     if query_result.is_err() {
@@ -186,43 +444,137 @@ pub async fn edit_record_store(
 
     let record_store = query_result.unwrap();
 
-    // modify the record store at the provided id
+    // nothing to change, nothing to record
+    if body.is_empty() {
+        return Ok(record_store);
+    }
 
-    let query_result = sqlx::query_as!(
-            RecordStoreModel,
-            "UPDATE record_stores SET store_name = $1, store_address = $2, store_city = $3, store_state = $4, 
-            store_zip = $5, phone_number = $6, website = $7 WHERE record_store_id = $8 RETURNING *", 
-            body.store_name.to_owned().unwrap_or(record_store.store_name),
-            body.store_address.to_owned().unwrap_or(record_store.store_address),
-            body.store_city.to_owned().unwrap_or(record_store.store_city),
-            body.store_state.to_owned().unwrap_or(record_store.store_state),
-            body.store_zip.to_owned().unwrap_or(record_store.store_zip),
-            body.phone_number.to_owned().unwrap_or(record_store.phone_number.unwrap()),
-            body.website.to_owned().unwrap_or(record_store.website.unwrap()),
-            id,
-        )
-        .fetch_one(&data.db)
-        .await;
+    if let Some(time_zone) = &body.time_zone {
+        check_time_zone(&mut *conn, time_zone).await?;
+    }
+    check_schedule(
+        body.opening_hours.as_deref().unwrap_or_default(),
+        body.hour_exceptions.as_deref().unwrap_or_default(),
+    )?;
 
-    match query_result {
-        // no errors -> respond with the record store
-        Ok(record_store) => {
-            let record_store_response = serde_json::json!(
-            {
-                "status": "success",
-                "record_store": record_store
-            });
+    let previous_store = record_store_snapshot(conn, &record_store)
+        .await
+        .map_err(internal_error)?;
 
-            println!("PATCH: editing {} store details", record_store.store_name);
+    if let Some(opening_hours) = &body.opening_hours {
+        replace_opening_hours(conn, id, opening_hours)
+            .await
+            .map_err(internal_error)?;
+    }
+    if let Some(hour_exceptions) = &body.hour_exceptions {
+        replace_hour_exceptions(conn, id, hour_exceptions)
+            .await
+            .map_err(internal_error)?;
+    }
 
-            Ok((StatusCode::OK, Json(record_store_response)))
+    // a store that moved zip codes without new coordinates moves to the zip's centroid
+    if let Some(store_zip) = &body.store_zip {
+        if body.latitude.is_none() && body.longitude.is_none() {
+            let (latitude, longitude) = geocode_zip(store_zip).unzip();
+            body.latitude = Some(latitude);
+            body.longitude = Some(longitude);
         }
+    }
+
+    // a schedule only edit leaves the record_stores row alone
+    let editor_id = body.editor_id;
+    let record_store = if body.changes_columns() {
+        update_record_store_columns(conn, id, body).await?
+    } else {
+        record_store
+    };
+
+    let current_store = record_store_snapshot(conn, &record_store)
+        .await
+        .map_err(internal_error)?;
+
+    save_revision(
+        conn,
+        "record_store",
+        id,
+        editor_id,
+        &previous_store,
+        &current_store,
+        None,
+    )
+    .await
+    .map_err(internal_error)?;
+
+    Ok(record_store)
+}
 
-        Err(err) => Err((
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(json!({"status": "error", "message": format!("{:?}", err)})),
-        )),
+/// update_record_store_columns
+/// modify only the columns supplied for the record store at the provided id
+async fn update_record_store_columns(
+    conn: &mut PgConnection,
+    id: Uuid,
+    body: UpdateRecordStoreSchema,
+) -> Result<RecordStoreModel, (StatusCode, Json<serde_json::Value>)> {
+    let mut update_query = QueryBuilder::<Postgres>::new("UPDATE record_stores SET ");
+    let mut columns = update_query.separated(", ");
+
+    if let Some(store_name) = body.store_name {
+        columns
+            .push("store_name = ")
+            .push_bind_unseparated(store_name);
+    }
+    if let Some(store_address) = body.store_address {
+        columns
+            .push("store_address = ")
+            .push_bind_unseparated(store_address);
+    }
+    if let Some(store_city) = body.store_city {
+        columns
+            .push("store_city = ")
+            .push_bind_unseparated(store_city);
+    }
+    if let Some(store_state) = body.store_state {
+        columns
+            .push("store_state = ")
+            .push_bind_unseparated(store_state);
     }
+    if let Some(store_zip) = body.store_zip {
+        columns
+            .push("store_zip = ")
+            .push_bind_unseparated(store_zip);
+    }
+    if let Some(phone_number) = body.phone_number {
+        columns
+            .push("phone_number = ")
+            .push_bind_unseparated(phone_number);
+    }
+    if let Some(website) = body.website {
+        columns.push("website = ").push_bind_unseparated(website);
+    }
+    if let Some(latitude) = body.latitude {
+        columns.push("latitude = ").push_bind_unseparated(latitude);
+    }
+    if let Some(longitude) = body.longitude {
+        columns
+            .push("longitude = ")
+            .push_bind_unseparated(longitude);
+    }
+    if let Some(time_zone) = body.time_zone {
+        columns
+            .push("time_zone = ")
+            .push_bind_unseparated(time_zone);
+    }
+
+    update_query
+        .push(" WHERE record_store_id = ")
+        .push_bind(id)
+        .push(" RETURNING *");
+
+    update_query
+        .build_query_as::<RecordStoreModel>()
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(internal_error)
 }
 
 pub async fn delete_record_store(
@@ -253,6 +605,7 @@ pub async fn delete_record_store(
 // USER RECORD STORE ENDPOINTS:
 pub async fn get_user_record_stores(
     Path(user_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
     State(data): State<Arc<AppState>>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
     // check for the user.
@@ -293,8 +646,10 @@ pub async fn get_user_record_stores(
     // query for those sweet tunes you've collected
     let stores_query = sqlx::query_as!(
         RecordStoreModel,
-        "SELECT * FROM record_stores WHERE record_store_id = ANY($1)",
+        "SELECT * FROM record_stores WHERE record_store_id = ANY($1)
+        AND ($2::bool IS NOT TRUE OR store_is_open(record_store_id, NOW()))",
         &user_stores_query,
+        opts.open_now,
     )
     .fetch_all(&data.db)
     .await;
@@ -361,6 +716,16 @@ pub async fn add_existing_record_store(
 
                     match user_record_store_insert {
                         Ok(user_record_store) => {
+                            record_activity(
+                                &data.db,
+                                user_record_store.user_key,
+                                "favorited_store",
+                                None,
+                                Some(existing_record_store.record_store_id),
+                                None,
+                            )
+                            .await;
+
                             let user_wished_created_response = serde_json::json!({
                                 "status": "success",
                                 "user_id": user_record_store.user_key,
@@ -419,21 +784,44 @@ pub async fn add_user_record_store(
     match user_query_check {
         // yay! found a user! let's add an awesome shop
         Ok(found_user) => {
+            if let Err(error_response) = check_coordinates(&body) {
+                return error_response;
+            }
+            let (latitude, longitude) = store_coordinates(&body);
+            let time_zone = body.time_zone.as_deref().unwrap_or(DEFAULT_TIME_ZONE);
+            if let Err(error_response) = check_time_zone(&data.db, time_zone).await {
+                return error_response;
+            }
+
+            // a store that's already listed is added to the user's stores instead of a duplicate
+            let matching_store = match find_matching_store(&data.db, &body).await {
+                Ok(matching_store) => matching_store,
+                Err(e) => return internal_error(e),
+            };
+
             // query for the new record_store insertion
-            let create_record_store = sqlx::query_as!(
-                RecordStoreModel,
-                "INSERT INTO record_stores (store_name, store_address, store_city, store_state, store_zip, phone_number, website)
-                VALUES ($1, $2, $3, $4, $5, $6, $7) RETURNING *",
-                body.store_name,
-                body.store_address,
-                body.store_city,
-                body.store_state,
-                body.store_zip,
-                body.phone_number.unwrap_or("".to_string()),
-                body.website.unwrap_or("".to_string()),
-            )
-            .fetch_one(&data.db)
-            .await;
+            let create_record_store = match matching_store {
+                Some(found_store) => Ok(found_store),
+                None => {
+                    sqlx::query_as!(
+                        RecordStoreModel,
+                        "INSERT INTO record_stores (store_name, store_address, store_city, store_state, store_zip, phone_number, website, latitude, longitude, time_zone)
+                        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING *",
+                        body.store_name,
+                        body.store_address,
+                        body.store_city,
+                        body.store_state,
+                        body.store_zip,
+                        body.phone_number.unwrap_or("".to_string()),
+                        body.website.unwrap_or("".to_string()),
+                        latitude,
+                        longitude,
+                        time_zone,
+                    )
+                    .fetch_one(&data.db)
+                    .await
+                }
+            };
 
             // hopefully I get a record model back.
 
@@ -441,7 +829,9 @@ pub async fn add_user_record_store(
                 Ok(created_record_store) => {
                     // add this to the user_records table by associated user_id
                     let user_record_store_insert_query = sqlx::query!(
-                        "INSERT INTO user_record_stores ( user_key, record_store_id) VALUES ($1, $2) RETURNING user_key, record_store_id",
+                        r#"INSERT INTO user_record_stores ( user_key, record_store_id) VALUES ($1, $2)
+                        ON CONFLICT (user_key, record_store_id) DO UPDATE SET user_key = EXCLUDED.user_key
+                        RETURNING user_key, record_store_id, (xmax = 0) AS "inserted!""#,
                         found_user.user_id,
                         created_record_store.record_store_id,
                     )
@@ -450,6 +840,19 @@ pub async fn add_user_record_store(
 
                     match user_record_store_insert_query {
                         Ok(inserted_user_store) => {
+                            // a store that was already a favorite isn't news to followers
+                            if inserted_user_store.inserted {
+                                record_activity(
+                                    &data.db,
+                                    inserted_user_store.user_key,
+                                    "favorited_store",
+                                    None,
+                                    Some(created_record_store.record_store_id),
+                                    None,
+                                )
+                                .await;
+                            }
+
                             let created_store_response = serde_json::json!({
                                 "status": "success",
                                 "user_id": inserted_user_store.user_key,
@@ -7,18 +7,55 @@ use axum::{
     Json,
 };
 use bigdecimal::BigDecimal;
+use sqlx::{PgConnection, PgExecutor, Pool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
 use serde_json::json;
 
 use crate::{
+    handlers::{
+        activity::record_activity,
+        alerts::match_wishlist_alerts,
+        internal_error,
+        moderation::submit_suggestion,
+        revisions::{find_editor, save_revision},
+        users::find_user,
+    },
     models::{
         record::{CreateRecordSchema, FilterOptions, RecordModel, UpdateRecordSchema},
-        user::{PatchUserRecord, PutUserRecord, UserModel},
+        user::{
+            PatchUserRecord, PutWishlistRecord, UpdateWishlistRecordSchema, UserModel,
+            WishlistRecordModel,
+        },
     },
     AppState,
 };
 
+/// check_record_exists:
+/// 404 for an unknown record_id
+pub async fn check_record_exists(
+    db: &Pool<Postgres>,
+    record_id: Uuid,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let record_query = sqlx::query_scalar!(
+        "SELECT record_id FROM records WHERE record_id = $1",
+        record_id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(internal_error)?;
+
+    if record_query.is_none() {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("record_id {} not found", record_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    }
+
+    Ok(())
+}
+
 /// GET all records from the database
 pub async fn list_all_records(
     Query(opts): Query<FilterOptions>,
@@ -101,19 +138,62 @@ pub async fn find_record(
 }
 
 /// edit_record
-/// deref leaves the original values in place as needed for options and passes the values
-/// within the struct attributes
+/// edits from trusted users are applied right away, everyone else's
+/// land in the suggested edit queue for a moderator to approve.
 pub async fn edit_record(
     Path(id): Path<Uuid>,
     State(data): State<Arc<AppState>>,
     Json(body): Json<UpdateRecordSchema>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let editor = find_editor(&data.db, body.editor_id).await?;
+
+    if !editor.as_ref().is_some_and(UserModel::is_trusted) {
+        let suggestion =
+            submit_suggestion(&data.db, "record", id, body.editor_id, json!(body)).await?;
+
+        println!("PATCH: queued edit to record_id {} for review", id);
+
+        return Ok((
+            StatusCode::ACCEPTED,
+            Json(json!({
+                "status": "success",
+                "message": "edit submitted for moderator review",
+                "suggestion": suggestion
+            })),
+        ));
+    }
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+    let record = apply_record_edit(&mut tx, id, body).await?;
+    tx.commit().await.map_err(internal_error)?;
+
+    let record_response = serde_json::json!(
+    {
+        "status": "success",
+        "record": record
+    });
+
+    println!("PATCH: edited {} by {}", record.title, record.artist);
+
+    Ok((StatusCode::OK, Json(record_response)))
+}
+
+/// apply_record_edit
+/// updates only the fields present in the patch, omitted fields keep their values
+/// and null clears the nullable ones. every change is kept in the record's edit history.
+/// shared by edit_record and approved suggestions.
+pub async fn apply_record_edit(
+    conn: &mut PgConnection,
+    id: Uuid,
+    body: UpdateRecordSchema,
+) -> Result<RecordModel, (StatusCode, Json<serde_json::Value>)> {
+    // lock the row so revisions are numbered in the order edits land
     let query_result = sqlx::query_as!(
         RecordModel,
-        "SELECT * FROM records WHERE record_id = $1",
+        "SELECT * FROM records WHERE record_id = $1 FOR UPDATE",
         id
     )
-    .fetch_one(&data.db)
+    .fetch_one(&mut *conn)
     .await;
 
     if query_result.is_err() {
@@ -128,37 +208,81 @@ pub async fn edit_record(
 
     let record = query_result.unwrap();
 
-    // modify the record store at the provided id
+    // nothing to change, nothing to record
+    if body.is_empty() {
+        return Ok(record);
+    }
+
+    let previous_record = json!(record);
 
-    let query_result = sqlx::query_as!(
-            RecordModel,
-            "UPDATE records SET artist = $1, title = $2, released = $3, genre = $4, 
-            format = $5, price = $6, label = $7, duration_length = $8 WHERE record_id = $9 RETURNING *", 
-            body.artist.to_owned().unwrap_or(record.artist),
-            body.title.to_owned().unwrap_or(record.title),
-            body.released.to_owned().unwrap_or(record.released),
-            &combine_supplied_genres(body.genre),
-            body.format.as_deref().unwrap_or(record.format.as_deref().unwrap_or("LP")),
-            body.price.unwrap_or(record.price.unwrap_or(BigDecimal::from(0))),
-            body.label.unwrap_or(record.label),
-            body.duration_length.unwrap_or(record.duration_length),
-            id,
-        )
-        .fetch_one(&data.db)
+    // a null genre clears the column, otherwise it's mapped onto the taxonomy
+    let genre = match body.genre {
+        Some(Some(genre)) => Some(Some(
+            combine_supplied_genres(&mut *conn, Some(genre))
+                .await
+                .map_err(internal_error)?,
+        )),
+        Some(None) => Some(None),
+        None => None,
+    };
+
+    // modify only the columns supplied for the record at the provided id
+    let mut update_query = QueryBuilder::<Postgres>::new("UPDATE records SET ");
+    let mut columns = update_query.separated(", ");
+
+    if let Some(artist) = body.artist {
+        columns.push("artist = ").push_bind_unseparated(artist);
+    }
+    if let Some(title) = body.title {
+        columns.push("title = ").push_bind_unseparated(title);
+    }
+    if let Some(released) = body.released {
+        columns.push("released = ").push_bind_unseparated(released);
+    }
+    if let Some(genre) = genre {
+        columns.push("genre = ").push_bind_unseparated(genre);
+    }
+    if let Some(format) = body.format {
+        columns.push("format = ").push_bind_unseparated(format);
+    }
+    if let Some(price) = body.price {
+        columns.push("price = ").push_bind_unseparated(price);
+    }
+    if let Some(label) = body.label {
+        columns.push("label = ").push_bind_unseparated(label);
+    }
+    if let Some(duration_length) = body.duration_length {
+        columns
+            .push("duration_length = ")
+            .push_bind_unseparated(duration_length);
+    }
+
+    update_query
+        .push(" WHERE record_id = ")
+        .push_bind(id)
+        .push(" RETURNING *");
+
+    let query_result = update_query
+        .build_query_as::<RecordModel>()
+        .fetch_one(&mut *conn)
         .await;
 
     match query_result {
-        // no errors -> respond with the record store
+        // no errors -> keep the revision
         Ok(record) => {
-            let record_response = serde_json::json!(
-            {
-                "status": "success",
-                "record": record
-            });
-
-            println!("PATCH: edited {} by {}", record.title, record.artist);
+            save_revision(
+                conn,
+                "record",
+                id,
+                body.editor_id,
+                &previous_record,
+                &json!(record),
+                None,
+            )
+            .await
+            .map_err(internal_error)?;
 
-            Ok((StatusCode::OK, Json(record_response)))
+            Ok(record)
         }
 
         Err(err) => Err((
@@ -169,11 +293,36 @@ pub async fn edit_record(
 }
 
 /// combine_supplied_genres:
-/// unwraps the Vec<String> and takes it's values.
-/// or supplies and empty string vec if it fails
-/// join supplies the commas for PostgreSQL Array
-pub fn combine_supplied_genres(record_genres: Option<Vec<String>>) -> Vec<String> {
-    record_genres.unwrap_or_default()
+/// unwraps the Vec<String> and maps every genre onto the genre taxonomy through
+/// its aliases, so "Jazz Funk" and "jazz-funk" are both stored as "Jazz-Funk".
+/// unknown genres are kept as supplied and duplicates are dropped, keeping the order.
+pub async fn combine_supplied_genres<'e>(
+    executor: impl PgExecutor<'e>,
+    record_genres: Option<Vec<String>>,
+) -> Result<Vec<String>, sqlx::Error> {
+    let supplied_genres = record_genres.unwrap_or_default();
+
+    if supplied_genres.is_empty() {
+        return Ok(supplied_genres);
+    }
+
+    let genres = sqlx::query_scalar!(
+        r#"SELECT COALESCE(array_agg(canonical.genre_name ORDER BY canonical.position), '{}') AS "genres!"
+        FROM (
+            SELECT COALESCE(g.genre_name, btrim(supplied.genre_name)) AS genre_name,
+                MIN(supplied.position) AS position
+            FROM unnest($1::TEXT[]) WITH ORDINALITY AS supplied (genre_name, position)
+            LEFT JOIN genre_aliases a ON a.alias = normalize_catalog_text(supplied.genre_name)
+            LEFT JOIN genres g ON g.genre_id = a.genre_id
+            WHERE btrim(supplied.genre_name) <> ''
+            GROUP BY 1
+        ) canonical"#,
+        &supplied_genres
+    )
+    .fetch_one(executor)
+    .await?;
+
+    Ok(genres)
 }
 
 /// POST add another record:
@@ -184,6 +333,10 @@ pub async fn create_new_record(
     State(data): State<Arc<AppState>>,
     Json(body): Json<CreateRecordSchema>,
 ) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let genres = combine_supplied_genres(&data.db, body.genre)
+        .await
+        .map_err(internal_error)?;
+
     // query for the new record insertion
     let query_result = sqlx::query_as!(
         RecordModel,
@@ -192,7 +345,7 @@ pub async fn create_new_record(
         body.artist.to_string(),
         body.title.to_string(),
         body.released,
-        &combine_supplied_genres(body.genre),
+        &genres,
         // unwrap if not supplied
         body.format.as_deref().unwrap_or("LP"),
         // if not supplied create empty value
@@ -286,11 +439,11 @@ pub async fn get_users_wishlist(
     }
 
     // query for tunes users dream of owning on vinyl
-    let record_query = sqlx::query_as!(
-        RecordModel,
-        "SELECT * FROM records WHERE record_id = ANY($1)",
-        &user_wishlist_query,
+    let record_query = sqlx::query_as::<_, WishlistRecordModel>(
+        "SELECT r.*, w.target_price, w.added_at FROM user_wishlist w
+        JOIN records r USING (record_id) WHERE w.user_id = $1",
     )
+    .bind(user_id)
     .fetch_all(&data.db)
     .await;
 
@@ -334,6 +487,11 @@ pub async fn add_to_user_wishlist(
     match user_query_check {
         // yay! found a user! let's add some sweet music
         Ok(found_user) => {
+            let genres = match combine_supplied_genres(&data.db, body.genre).await {
+                Ok(genres) => genres,
+                Err(e) => return internal_error(e),
+            };
+
             // query for the new record insertion
             let create_record_result = sqlx::query_as!(
                 RecordModel,
@@ -342,7 +500,7 @@ pub async fn add_to_user_wishlist(
                 body.artist,
                 body.title,
                 body.released,
-                &combine_supplied_genres(body.genre),
+                &genres,
                 // unwrap if not supplied
                 body.format.as_deref().unwrap_or("LP"),
                 // if not supplied create empty value
@@ -368,6 +526,16 @@ pub async fn add_to_user_wishlist(
 
                     match user_wishlist_record {
                         Ok(created_wish_list_record) => {
+                            record_activity(
+                                &data.db,
+                                created_wish_list_record.user_id,
+                                "wished",
+                                Some(created_record.record_id),
+                                None,
+                                None,
+                            )
+                            .await;
+
                             let created_wishlist_response = serde_json::json!({
                                 "status": "success",
                                 "records_collected": "1",
@@ -417,8 +585,12 @@ pub async fn add_to_user_wishlist(
 pub async fn put_wishlist_record(
     Path(user_id): Path<Uuid>,
     State(data): State<Arc<AppState>>,
-    Json(body): Json<PutUserRecord>,
+    Json(body): Json<PutWishlistRecord>,
 ) -> impl IntoResponse {
+    if let Err(error_response) = check_target_price(body.target_price.as_ref()) {
+        return error_response;
+    }
+
     //query for the user if they even exist...
     let user_query_check =
         sqlx::query_as!(UserModel, "SELECT * FROM users WHERE user_id = $1", user_id)
@@ -427,8 +599,9 @@ pub async fn put_wishlist_record(
 
     // check for the existing record on the wishlist
     if let Ok(Some(_)) = sqlx::query!(
-        "SELECT * FROM user_wishlist WHERE record_id = $1",
-        body.record_id
+        "SELECT * FROM user_wishlist WHERE record_id = $1 AND user_id = $2",
+        body.record_id,
+        user_id
     )
     .fetch_optional(&data.db)
     .await
@@ -461,20 +634,40 @@ pub async fn put_wishlist_record(
                 Ok(wished_record) => {
                     // add this to the user_records table by associated user_id
                     let user_wishlist_record_query = sqlx::query!(
-                        "INSERT INTO user_wishlist ( user_id, record_id) VALUES ($1, $2) RETURNING user_id, record_id, user_wish_list_id",
+                        "INSERT INTO user_wishlist ( user_id, record_id, target_price) VALUES ($1, $2, $3) RETURNING user_id, record_id, user_wish_list_id, target_price",
                         found_user.user_id,
                         wished_record.record_id,
+                        body.target_price,
                     )
                     .fetch_one(&data.db)
                     .await;
 
                     match user_wishlist_record_query {
                         Ok(wished_user_record) => {
+                            // the record may already be sitting in a store's bins
+                            let alerts_sent =
+                                match match_wishlist_alerts(&data.db, None, Some(user_id)).await {
+                                    Ok(alerts_sent) => alerts_sent,
+                                    Err(e) => return internal_error(e),
+                                };
+
+                            record_activity(
+                                &data.db,
+                                wished_user_record.user_id,
+                                "wished",
+                                Some(wished_record.record_id),
+                                None,
+                                None,
+                            )
+                            .await;
+
                             let user_wished_created_response = serde_json::json!({
                                 "status": "success",
                                 "records_collected": "1",
                                 "user_id": wished_user_record.user_id,
                                 "user_wish_list_id": wished_user_record.user_wish_list_id,
+                                "target_price": wished_user_record.target_price,
+                                "alerts_sent": alerts_sent,
                                 "record": wished_record,
                             });
 
@@ -517,6 +710,79 @@ pub async fn put_wishlist_record(
     }
 }
 
+/// check_target_price:
+/// a wishlist target price can't be negative
+fn check_target_price(
+    target_price: Option<&BigDecimal>,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if target_price.is_some_and(|target_price| *target_price < BigDecimal::from(0)) {
+        let error_response = json!({
+            "status": "fail",
+            "message": "target_price can't be negative"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    Ok(())
+}
+
+/// PATCH the target price of a wishlist record, null alerts at any price
+pub async fn edit_wishlist_record(
+    Path((user_id, record_id)): Path<(Uuid, Uuid)>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<UpdateWishlistRecordSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_user(&data.db, user_id).await?;
+
+    let Some(target_price) = body.target_price else {
+        let error_response = json!({
+            "status": "fail",
+            "message": "nothing to change, supply a target_price"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    };
+    check_target_price(target_price.as_ref())?;
+
+    let wishlist_record = sqlx::query_as::<_, WishlistRecordModel>(
+        "WITH updated AS (
+            UPDATE user_wishlist SET target_price = $3
+            WHERE user_id = $1 AND record_id = $2 RETURNING *
+        )
+        SELECT r.*, updated.target_price, updated.added_at
+        FROM updated JOIN records r USING (record_id)",
+    )
+    .bind(user_id)
+    .bind(record_id)
+    .bind(target_price)
+    .fetch_optional(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(wishlist_record) = wishlist_record else {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("No wish_lists record found for user_id: {} with record_id: {}", user_id, record_id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    // a higher target price can bring listings already in stock into range
+    let alerts_sent = match_wishlist_alerts(&data.db, None, Some(user_id))
+        .await
+        .map_err(internal_error)?;
+
+    println!(
+        "PATCH: target price for {} on user_id: {} wishlist",
+        wishlist_record.record.title, user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "alerts_sent": alerts_sent,
+        "record": wishlist_record,
+    })))
+}
+
 // DELETE a specific record from the wishlist
 pub async fn remove_wishlist_record(
     Path(user_id): Path<Uuid>,
@@ -0,0 +1,528 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{NaiveDate, Utc};
+use serde_json::json;
+use sqlx::{PgExecutor, Postgres, QueryBuilder};
+use uuid::Uuid;
+
+use crate::{
+    handlers::{
+        activity::record_activity, internal_error, record_stores::check_store_exists,
+        records::check_record_exists, users::find_user,
+    },
+    models::review::{
+        CreateRecordReviewSchema, CreateStoreReviewSchema, DeleteReviewOptions, FilterOptions,
+        RecordRatingSummary, RecordReviewModel, StoreRatingSummary, StoreReviewModel,
+        UpdateRecordReviewSchema, UpdateStoreReviewSchema,
+    },
+    AppState,
+};
+
+/// check_review:
+/// star ratings run from 1 to 5 and nobody visits a store in the future
+fn check_review(
+    ratings: &[(&str, Option<i16>)],
+    visited_on: Option<NaiveDate>,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let fail = |message: String| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"status": "fail", "message": message})),
+        )
+    };
+
+    if let Some((name, _)) = ratings
+        .iter()
+        .find(|(_, rating)| rating.is_some_and(|rating| !(1..=5).contains(&rating)))
+    {
+        return Err(fail(format!("{} must be between 1 and 5 stars", name)));
+    }
+
+    if visited_on.is_some_and(|visited_on| visited_on > Utc::now().date_naive()) {
+        return Err(fail("visited_on can't be in the future".to_string()));
+    }
+
+    Ok(())
+}
+
+/// review_not_found:
+/// the 404 for a review that doesn't exist or belongs to something else
+fn review_not_found(review_id: Uuid) -> (StatusCode, Json<serde_json::Value>) {
+    let error_response = json!({
+        "status": "fail",
+        "message": format!("review_id {} not found", review_id)
+    });
+    (StatusCode::NOT_FOUND, Json(error_response))
+}
+
+/// fetch_store_review:
+/// a store review along with the reviewer's user name
+async fn fetch_store_review<'e>(
+    executor: impl PgExecutor<'e>,
+    record_store_id: Uuid,
+    review_id: Uuid,
+) -> Result<StoreReviewModel, (StatusCode, Json<serde_json::Value>)> {
+    let review = sqlx::query_as!(
+        StoreReviewModel,
+        "SELECT r.review_id, r.record_store_id, r.user_id, u.user_name, r.rating, r.review_text,
+            r.visited_on, r.selection_rating, r.price_rating, r.staff_rating, r.created_at, r.updated_at
+        FROM store_reviews r JOIN users u USING (user_id)
+        WHERE r.review_id = $1 AND r.record_store_id = $2",
+        review_id,
+        record_store_id
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(internal_error)?;
+
+    review.ok_or_else(|| review_not_found(review_id))
+}
+
+/// GET a store's reviews, newest first, with its average ratings
+pub async fn get_store_reviews(
+    Path(record_store_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_store_exists(&data.db, record_store_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let reviews = sqlx::query_as!(
+        StoreReviewModel,
+        "SELECT r.review_id, r.record_store_id, r.user_id, u.user_name, r.rating, r.review_text,
+            r.visited_on, r.selection_rating, r.price_rating, r.staff_rating, r.created_at, r.updated_at
+        FROM store_reviews r JOIN users u USING (user_id)
+        WHERE r.record_store_id = $1
+        ORDER BY r.created_at DESC LIMIT $2 OFFSET $3",
+        record_store_id,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let rating = sqlx::query_as!(
+        StoreRatingSummary,
+        r#"SELECT ROUND(AVG(rating), 2)::DOUBLE PRECISION AS average_rating,
+            ROUND(AVG(selection_rating), 2)::DOUBLE PRECISION AS average_selection_rating,
+            ROUND(AVG(price_rating), 2)::DOUBLE PRECISION AS average_price_rating,
+            ROUND(AVG(staff_rating), 2)::DOUBLE PRECISION AS average_staff_rating,
+            COUNT(*) AS "review_count!"
+        FROM store_reviews WHERE record_store_id = $1"#,
+        record_store_id
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} reviews for record store {}",
+        reviews.len(),
+        record_store_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "rating": rating,
+        "results": reviews.len(),
+        "reviews": reviews,
+    })))
+}
+
+/// POST a user's review of a store, each user reviews a store once
+pub async fn create_store_review(
+    Path(record_store_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<CreateStoreReviewSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_review(
+        &[
+            ("rating", Some(body.rating)),
+            ("selection_rating", body.selection_rating),
+            ("price_rating", body.price_rating),
+            ("staff_rating", body.staff_rating),
+        ],
+        body.visited_on,
+    )?;
+    let reviewer = find_user(&data.db, body.user_id).await?;
+    check_store_exists(&data.db, record_store_id).await?;
+
+    let insert_result = sqlx::query_scalar!(
+        "INSERT INTO store_reviews (record_store_id, user_id, rating, review_text, visited_on,
+            selection_rating, price_rating, staff_rating)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8) RETURNING review_id",
+        record_store_id,
+        body.user_id,
+        body.rating,
+        body.review_text,
+        body.visited_on,
+        body.selection_rating,
+        body.price_rating,
+        body.staff_rating
+    )
+    .fetch_one(&data.db)
+    .await;
+
+    let review_id = match insert_result {
+        Ok(review_id) => review_id,
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("{} has already reviewed this store, edit the existing review instead", reviewer.user_name)
+            });
+            return Err((StatusCode::CONFLICT, Json(error_response)));
+        }
+        Err(e) => return Err(internal_error(e)),
+    };
+
+    let review = fetch_store_review(&data.db, record_store_id, review_id).await?;
+
+    record_activity(
+        &data.db,
+        review.user_id,
+        "reviewed_store",
+        None,
+        Some(record_store_id),
+        Some(review_id),
+    )
+    .await;
+
+    println!(
+        "POST: {} reviewed record store {}",
+        review.user_name, record_store_id
+    );
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "review": review,
+        })),
+    ))
+}
+
+/// PATCH a store review, only the reviewer can change it
+pub async fn edit_store_review(
+    Path((record_store_id, review_id)): Path<(Uuid, Uuid)>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<UpdateStoreReviewSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_review(
+        &[
+            ("rating", body.rating),
+            ("selection_rating", body.selection_rating.flatten()),
+            ("price_rating", body.price_rating.flatten()),
+            ("staff_rating", body.staff_rating.flatten()),
+        ],
+        body.visited_on.flatten(),
+    )?;
+
+    let review = fetch_store_review(&data.db, record_store_id, review_id).await?;
+
+    if review.user_id != body.user_id {
+        let error_response = json!({
+            "status": "fail",
+            "message": "only the reviewer can edit a review"
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    let mut update_query = QueryBuilder::<Postgres>::new("UPDATE store_reviews SET ");
+    let mut columns = update_query.separated(", ");
+
+    columns.push("updated_at = NOW()");
+    if let Some(rating) = body.rating {
+        columns.push("rating = ").push_bind_unseparated(rating);
+    }
+    if let Some(review_text) = body.review_text {
+        columns
+            .push("review_text = ")
+            .push_bind_unseparated(review_text);
+    }
+    if let Some(visited_on) = body.visited_on {
+        columns
+            .push("visited_on = ")
+            .push_bind_unseparated(visited_on);
+    }
+    if let Some(selection_rating) = body.selection_rating {
+        columns
+            .push("selection_rating = ")
+            .push_bind_unseparated(selection_rating);
+    }
+    if let Some(price_rating) = body.price_rating {
+        columns
+            .push("price_rating = ")
+            .push_bind_unseparated(price_rating);
+    }
+    if let Some(staff_rating) = body.staff_rating {
+        columns
+            .push("staff_rating = ")
+            .push_bind_unseparated(staff_rating);
+    }
+
+    update_query
+        .push(" WHERE review_id = ")
+        .push_bind(review_id);
+
+    update_query
+        .build()
+        .execute(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    let review = fetch_store_review(&data.db, record_store_id, review_id).await?;
+
+    println!("PATCH: updated store review {}", review_id);
+
+    Ok(Json(json!({
+        "status": "success",
+        "review": review,
+    })))
+}
+
+/// DELETE a store review, by the reviewer or a moderator
+pub async fn delete_store_review(
+    Path((record_store_id, review_id)): Path<(Uuid, Uuid)>,
+    Query(opts): Query<DeleteReviewOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = find_user(&data.db, opts.user_id).await?;
+    let review = fetch_store_review(&data.db, record_store_id, review_id).await?;
+
+    if review.user_id != user.user_id && !user.is_moderator() {
+        let error_response = json!({
+            "status": "fail",
+            "message": "only the reviewer or a moderator can delete a review"
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    sqlx::query!("DELETE FROM store_reviews WHERE review_id = $1", review_id)
+        .execute(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    println!("DELETE: removed store review: {}", review_id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// fetch_record_review:
+/// a record review along with the reviewer's user name
+async fn fetch_record_review<'e>(
+    executor: impl PgExecutor<'e>,
+    record_id: Uuid,
+    review_id: Uuid,
+) -> Result<RecordReviewModel, (StatusCode, Json<serde_json::Value>)> {
+    let review = sqlx::query_as!(
+        RecordReviewModel,
+        "SELECT r.review_id, r.record_id, r.user_id, u.user_name, r.rating, r.review_text,
+            r.created_at, r.updated_at
+        FROM record_reviews r JOIN users u USING (user_id)
+        WHERE r.review_id = $1 AND r.record_id = $2",
+        review_id,
+        record_id
+    )
+    .fetch_optional(executor)
+    .await
+    .map_err(internal_error)?;
+
+    review.ok_or_else(|| review_not_found(review_id))
+}
+
+/// GET a record's reviews, newest first, with its rating breakdown
+pub async fn get_record_reviews(
+    Path(record_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_record_exists(&data.db, record_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let reviews = sqlx::query_as!(
+        RecordReviewModel,
+        "SELECT r.review_id, r.record_id, r.user_id, u.user_name, r.rating, r.review_text,
+            r.created_at, r.updated_at
+        FROM record_reviews r JOIN users u USING (user_id)
+        WHERE r.record_id = $1
+        ORDER BY r.created_at DESC LIMIT $2 OFFSET $3",
+        record_id,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let rating = sqlx::query_as!(
+        RecordRatingSummary,
+        r#"SELECT ROUND(AVG(rating), 2)::DOUBLE PRECISION AS average_rating,
+            COUNT(*) AS "review_count!",
+            ARRAY[
+                COUNT(*) FILTER (WHERE rating = 1), COUNT(*) FILTER (WHERE rating = 2),
+                COUNT(*) FILTER (WHERE rating = 3), COUNT(*) FILTER (WHERE rating = 4),
+                COUNT(*) FILTER (WHERE rating = 5)
+            ] AS "star_counts!"
+        FROM record_reviews WHERE record_id = $1"#,
+        record_id
+    )
+    .fetch_one(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} reviews for record {}",
+        reviews.len(),
+        record_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "rating": rating,
+        "results": reviews.len(),
+        "reviews": reviews,
+    })))
+}
+
+/// POST a user's review of a record, each user reviews a record once
+pub async fn create_record_review(
+    Path(record_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<CreateRecordReviewSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_review(&[("rating", Some(body.rating))], None)?;
+    let reviewer = find_user(&data.db, body.user_id).await?;
+    check_record_exists(&data.db, record_id).await?;
+
+    let insert_result = sqlx::query_scalar!(
+        "INSERT INTO record_reviews (record_id, user_id, rating, review_text)
+        VALUES ($1, $2, $3, $4) RETURNING review_id",
+        record_id,
+        body.user_id,
+        body.rating,
+        body.review_text
+    )
+    .fetch_one(&data.db)
+    .await;
+
+    let review_id = match insert_result {
+        Ok(review_id) => review_id,
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("{} has already reviewed this record, edit the existing review instead", reviewer.user_name)
+            });
+            return Err((StatusCode::CONFLICT, Json(error_response)));
+        }
+        Err(e) => return Err(internal_error(e)),
+    };
+
+    let review = fetch_record_review(&data.db, record_id, review_id).await?;
+
+    record_activity(
+        &data.db,
+        review.user_id,
+        "reviewed_record",
+        Some(record_id),
+        None,
+        Some(review_id),
+    )
+    .await;
+
+    println!("POST: {} reviewed record {}", review.user_name, record_id);
+
+    Ok((
+        StatusCode::CREATED,
+        Json(json!({
+            "status": "success",
+            "review": review,
+        })),
+    ))
+}
+
+/// PATCH a record review, only the reviewer can change it
+pub async fn edit_record_review(
+    Path((record_id, review_id)): Path<(Uuid, Uuid)>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<UpdateRecordReviewSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_review(&[("rating", body.rating)], None)?;
+
+    let review = fetch_record_review(&data.db, record_id, review_id).await?;
+
+    if review.user_id != body.user_id {
+        let error_response = json!({
+            "status": "fail",
+            "message": "only the reviewer can edit a review"
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    let mut update_query = QueryBuilder::<Postgres>::new("UPDATE record_reviews SET ");
+    let mut columns = update_query.separated(", ");
+
+    columns.push("updated_at = NOW()");
+    if let Some(rating) = body.rating {
+        columns.push("rating = ").push_bind_unseparated(rating);
+    }
+    if let Some(review_text) = body.review_text {
+        columns
+            .push("review_text = ")
+            .push_bind_unseparated(review_text);
+    }
+
+    update_query
+        .push(" WHERE review_id = ")
+        .push_bind(review_id);
+
+    update_query
+        .build()
+        .execute(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    let review = fetch_record_review(&data.db, record_id, review_id).await?;
+
+    println!("PATCH: updated record review {}", review_id);
+
+    Ok(Json(json!({
+        "status": "success",
+        "review": review,
+    })))
+}
+
+/// DELETE a record review, by the reviewer or a moderator
+pub async fn delete_record_review(
+    Path((record_id, review_id)): Path<(Uuid, Uuid)>,
+    Query(opts): Query<DeleteReviewOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let user = find_user(&data.db, opts.user_id).await?;
+    let review = fetch_record_review(&data.db, record_id, review_id).await?;
+
+    if review.user_id != user.user_id && !user.is_moderator() {
+        let error_response = json!({
+            "status": "fail",
+            "message": "only the reviewer or a moderator can delete a review"
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    sqlx::query!("DELETE FROM record_reviews WHERE review_id = $1", review_id)
+        .execute(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    println!("DELETE: removed record review: {}", review_id);
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,488 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::{json, Map, Value};
+use sqlx::{PgConnection, Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    handlers::{
+        internal_error,
+        moderation::find_moderator,
+        store_hours::{record_store_snapshot, replace_hour_exceptions, replace_opening_hours},
+    },
+    models::{
+        record::RecordModel,
+        revision::{FilterOptions, RevertRevisionSchema, RevisionModel},
+        store::{HourExceptionModel, OpeningHoursModel, RecordStoreModel},
+        user::UserModel,
+    },
+    AppState,
+};
+
+/// find_editor:
+/// edits may be anonymous, but a supplied editor_id has to belong to a user
+pub async fn find_editor(
+    db: &Pool<Postgres>,
+    editor_id: Option<Uuid>,
+) -> Result<Option<UserModel>, (StatusCode, Json<serde_json::Value>)> {
+    let Some(editor_id) = editor_id else {
+        return Ok(None);
+    };
+
+    let editor_query = sqlx::query_as!(
+        UserModel,
+        "SELECT * FROM users WHERE user_id = $1",
+        editor_id
+    )
+    .fetch_optional(db)
+    .await
+    .map_err(internal_error)?;
+
+    match editor_query {
+        Some(editor) => Ok(Some(editor)),
+        None => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("editor_id {} not found", editor_id)
+            });
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+    }
+}
+
+/// diff_snapshots:
+/// lists every field that changed between two serialized rows as {"from", "to"}
+fn diff_snapshots(previous: &Value, current: &Value) -> Map<String, Value> {
+    let mut diff = Map::new();
+
+    if let Some(current_fields) = current.as_object() {
+        for (field, to) in current_fields {
+            let from = previous.get(field).unwrap_or(&Value::Null);
+            if from != to {
+                diff.insert(field.to_owned(), json!({"from": from, "to": to}));
+            }
+        }
+    }
+
+    diff
+}
+
+/// save_revision:
+/// stores the change between two snapshots of a record or record store.
+/// the first tracked edit of a row also stores its untouched state as revision 1
+/// so the original values can always be reverted to. edits that change nothing are skipped.
+pub async fn save_revision(
+    conn: &mut PgConnection,
+    entity_type: &str,
+    entity_id: Uuid,
+    editor_id: Option<Uuid>,
+    previous: &Value,
+    current: &Value,
+    reverted_to: Option<Uuid>,
+) -> Result<(), sqlx::Error> {
+    let diff = diff_snapshots(previous, current);
+
+    if diff.is_empty() && reverted_to.is_none() {
+        return Ok(());
+    }
+
+    let latest_revision = sqlx::query_scalar!(
+        "SELECT MAX(revision_number) FROM catalog_revisions WHERE entity_type = $1 AND entity_id = $2",
+        entity_type,
+        entity_id
+    )
+    .fetch_one(&mut *conn)
+    .await?
+    .unwrap_or(0);
+
+    // capture the state from before history was tracked
+    if latest_revision == 0 {
+        sqlx::query!(
+            "INSERT INTO catalog_revisions (entity_type, entity_id, revision_number, snapshot)
+            VALUES ($1, $2, 1, $3)",
+            entity_type,
+            entity_id,
+            previous
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    sqlx::query!(
+        "INSERT INTO catalog_revisions (entity_type, entity_id, revision_number, editor_id, snapshot, diff, reverted_to)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        entity_type,
+        entity_id,
+        latest_revision.max(1) + 1,
+        editor_id,
+        current,
+        Value::Object(diff),
+        reverted_to
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// fetch_history:
+/// newest revisions first, along with the editor's user_name
+async fn fetch_history(
+    db: &Pool<Postgres>,
+    entity_type: &str,
+    entity_id: Uuid,
+    opts: FilterOptions,
+) -> Result<Vec<RevisionModel>, sqlx::Error> {
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    sqlx::query_as!(
+        RevisionModel,
+        r#"SELECT r.revision_id, r.entity_type, r.entity_id, r.revision_number, r.editor_id,
+            u.user_name AS "editor_name?", r.snapshot, r.diff, r.reverted_to, r.created_at
+        FROM catalog_revisions r
+        LEFT JOIN users u ON u.user_id = r.editor_id
+        WHERE r.entity_type = $1 AND r.entity_id = $2
+        ORDER BY r.revision_number DESC
+        LIMIT $3 OFFSET $4"#,
+        entity_type,
+        entity_id,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// find_revision:
+/// a revision can only be reverted to by the entity it belongs to
+async fn find_revision(
+    conn: &mut PgConnection,
+    entity_type: &str,
+    entity_id: Uuid,
+    revision_id: Uuid,
+) -> Result<RevisionModel, (StatusCode, Json<serde_json::Value>)> {
+    let revision_query = sqlx::query_as!(
+        RevisionModel,
+        r#"SELECT r.revision_id, r.entity_type, r.entity_id, r.revision_number, r.editor_id,
+            u.user_name AS "editor_name?", r.snapshot, r.diff, r.reverted_to, r.created_at
+        FROM catalog_revisions r
+        LEFT JOIN users u ON u.user_id = r.editor_id
+        WHERE r.revision_id = $1 AND r.entity_type = $2 AND r.entity_id = $3"#,
+        revision_id,
+        entity_type,
+        entity_id
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(internal_error)?;
+
+    revision_query.ok_or_else(|| {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("revision_id {} not found for {} {}", revision_id, entity_type, entity_id)
+        });
+        (StatusCode::NOT_FOUND, Json(error_response))
+    })
+}
+
+/// GET the edit history of a record
+pub async fn get_record_history(
+    Path(id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let revisions = fetch_history(&data.db, "record", id, opts)
+        .await
+        .map_err(internal_error)?;
+
+    // history outlives deleted records, so only an unknown record is a miss
+    if revisions.is_empty() {
+        let record_check = sqlx::query!("SELECT record_id FROM records WHERE record_id = $1", id)
+            .fetch_optional(&data.db)
+            .await
+            .map_err(internal_error)?;
+
+        if record_check.is_none() {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("record_id {} not found", id)
+            });
+            return Err((StatusCode::NOT_FOUND, Json(error_response)));
+        }
+    }
+
+    println!(
+        "GET: returning {} revisions for record_id {}",
+        revisions.len(),
+        id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": revisions.len(),
+        "revisions": revisions,
+    })))
+}
+
+/// GET the edit history of a record store
+pub async fn get_record_store_history(
+    Path(id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let revisions = fetch_history(&data.db, "record_store", id, opts)
+        .await
+        .map_err(internal_error)?;
+
+    if revisions.is_empty() {
+        let store_check = sqlx::query!(
+            "SELECT record_store_id FROM record_stores WHERE record_store_id = $1",
+            id
+        )
+        .fetch_optional(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+        if store_check.is_none() {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("record_store_id {} not found", id)
+            });
+            return Err((StatusCode::NOT_FOUND, Json(error_response)));
+        }
+    }
+
+    println!(
+        "GET: returning {} revisions for record_store_id {}",
+        revisions.len(),
+        id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": revisions.len(),
+        "revisions": revisions,
+    })))
+}
+
+/// POST revert a record to a previous revision
+/// the revert itself is stored as a new revision, so it can be undone too.
+pub async fn revert_record(
+    Path(id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<RevertRevisionSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_moderator(&data.db, body.moderator_id).await?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let revision = find_revision(&mut tx, "record", id, body.revision_id).await?;
+
+    let current_record = sqlx::query_as!(
+        RecordModel,
+        "SELECT * FROM records WHERE record_id = $1 FOR UPDATE",
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(current_record) = current_record else {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("record_id {} not found", id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    let restored: RecordModel = serde_json::from_value(revision.snapshot).map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"status": "error", "message": format!("{:?}", e)})),
+        )
+    })?;
+
+    let query_result = sqlx::query_as!(
+        RecordModel,
+        "UPDATE records SET artist = $1, title = $2, released = $3, genre = $4,
+        format = $5, price = $6, label = $7, duration_length = $8 WHERE record_id = $9 RETURNING *",
+        restored.artist,
+        restored.title,
+        restored.released,
+        restored.genre.as_deref(),
+        restored.format,
+        restored.price,
+        restored.label,
+        restored.duration_length,
+        id,
+    )
+    .fetch_one(&mut *tx)
+    .await;
+
+    match query_result {
+        Ok(record) => {
+            save_revision(
+                &mut tx,
+                "record",
+                id,
+                Some(body.moderator_id),
+                &json!(current_record),
+                &json!(record),
+                Some(revision.revision_id),
+            )
+            .await
+            .map_err(internal_error)?;
+
+            tx.commit().await.map_err(internal_error)?;
+
+            println!(
+                "POST: reverted {} by {} to revision {}",
+                record.title, record.artist, revision.revision_number
+            );
+
+            Ok(Json(json!({
+                "status": "success",
+                "record": record,
+            })))
+        }
+        // the restored values collide with another record
+        Err(_) => Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "status": "fail",
+                "message": format!("record {} by {} already exists", restored.title, restored.artist)
+            })),
+        )),
+    }
+}
+
+/// POST revert a record store to a previous revision
+pub async fn revert_record_store(
+    Path(id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<RevertRevisionSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_moderator(&data.db, body.moderator_id).await?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let revision = find_revision(&mut tx, "record_store", id, body.revision_id).await?;
+
+    let current_store = sqlx::query_as!(
+        RecordStoreModel,
+        "SELECT * FROM record_stores WHERE record_store_id = $1 FOR UPDATE",
+        id
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    let Some(current_store) = current_store else {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("record_store_id {} not found", id)
+        });
+        return Err((StatusCode::NOT_FOUND, Json(error_response)));
+    };
+
+    let previous_store = record_store_snapshot(&mut tx, &current_store)
+        .await
+        .map_err(internal_error)?;
+
+    let snapshot_error = |e: serde_json::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({"status": "error", "message": format!("{:?}", e)})),
+        )
+    };
+
+    // revisions saved before stores had opening hours leave the schedule alone
+    if let Some(opening_hours) = revision.snapshot.get("opening_hours") {
+        let opening_hours: Vec<OpeningHoursModel> =
+            serde_json::from_value(opening_hours.clone()).map_err(snapshot_error)?;
+        replace_opening_hours(&mut tx, id, &opening_hours)
+            .await
+            .map_err(internal_error)?;
+    }
+    if let Some(hour_exceptions) = revision.snapshot.get("hour_exceptions") {
+        let hour_exceptions: Vec<HourExceptionModel> =
+            serde_json::from_value(hour_exceptions.clone()).map_err(snapshot_error)?;
+        replace_hour_exceptions(&mut tx, id, &hour_exceptions)
+            .await
+            .map_err(internal_error)?;
+    }
+
+    // as do revisions saved before stores had a time zone
+    let mut snapshot = revision.snapshot;
+    if snapshot.get("time_zone").is_none() {
+        snapshot["time_zone"] = json!(current_store.time_zone);
+    }
+
+    let restored: RecordStoreModel = serde_json::from_value(snapshot).map_err(snapshot_error)?;
+
+    let query_result = sqlx::query_as!(
+        RecordStoreModel,
+        "UPDATE record_stores SET store_name = $1, store_address = $2, store_city = $3, store_state = $4,
+        store_zip = $5, phone_number = $6, website = $7, latitude = $8, longitude = $9, time_zone = $10
+        WHERE record_store_id = $11 RETURNING *",
+        restored.store_name,
+        restored.store_address,
+        restored.store_city,
+        restored.store_state,
+        restored.store_zip,
+        restored.phone_number,
+        restored.website,
+        restored.latitude,
+        restored.longitude,
+        restored.time_zone,
+        id,
+    )
+    .fetch_one(&mut *tx)
+    .await;
+
+    match query_result {
+        Ok(record_store) => {
+            let current_store = record_store_snapshot(&mut tx, &record_store)
+                .await
+                .map_err(internal_error)?;
+
+            save_revision(
+                &mut tx,
+                "record_store",
+                id,
+                Some(body.moderator_id),
+                &previous_store,
+                &current_store,
+                Some(revision.revision_id),
+            )
+            .await
+            .map_err(internal_error)?;
+
+            tx.commit().await.map_err(internal_error)?;
+
+            println!(
+                "POST: reverted {} record store to revision {}",
+                record_store.store_name, revision.revision_number
+            );
+
+            Ok(Json(json!({
+                "status": "success",
+                "record_store": record_store,
+            })))
+        }
+        Err(_) => Err((
+            StatusCode::CONFLICT,
+            Json(json!({
+                "status": "fail",
+                "message": format!("Record store '{}' already exists.", restored.store_name)
+            })),
+        )),
+    }
+}
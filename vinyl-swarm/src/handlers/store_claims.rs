@@ -0,0 +1,392 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde_json::json;
+use sqlx::{PgConnection, PgExecutor, Pool, Postgres};
+use uuid::Uuid;
+
+use crate::{
+    handlers::{
+        internal_error, notifications::notify, record_stores::check_store_exists, users::find_user,
+    },
+    models::{
+        claim::{
+            ClaimFilterOptions, CreateStoreClaimSchema, FilterOptions, RejectStoreClaimSchema,
+            StoreClaimModel, StoreOwnerModel, VerifyStoreClaimSchema,
+        },
+        user::UserModel,
+    },
+    AppState,
+};
+
+/// find_admin:
+/// the acting user, FORBIDDEN unless they're an admin
+pub async fn find_admin(
+    db: &Pool<Postgres>,
+    admin_id: Uuid,
+) -> Result<UserModel, (StatusCode, Json<serde_json::Value>)> {
+    let user = find_user(db, admin_id).await?;
+
+    if !user.is_admin() {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("user {} is not an admin", user.user_name)
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    Ok(user)
+}
+
+/// store_owner_ids:
+/// the users with a verified claim on a store
+pub async fn store_owner_ids<'e>(
+    executor: impl PgExecutor<'e>,
+    record_store_id: Uuid,
+) -> Result<Vec<Uuid>, sqlx::Error> {
+    sqlx::query_scalar!(
+        "SELECT user_id FROM store_claims WHERE record_store_id = $1 AND claim_status = 'verified'",
+        record_store_id
+    )
+    .fetch_all(executor)
+    .await
+}
+
+/// check_store_manager:
+/// once a store has a verified owner only its owners and moderators manage it,
+/// anyone can manage a store nobody has claimed
+pub async fn check_store_manager(
+    db: &Pool<Postgres>,
+    record_store_id: Uuid,
+    user: &UserModel,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    if user.is_moderator() {
+        return Ok(());
+    }
+
+    let owner_ids = store_owner_ids(db, record_store_id)
+        .await
+        .map_err(internal_error)?;
+
+    if !owner_ids.is_empty() && !owner_ids.contains(&user.user_id) {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("record store {} is managed by its verified owners", record_store_id)
+        });
+        return Err((StatusCode::FORBIDDEN, Json(error_response)));
+    }
+
+    Ok(())
+}
+
+/// find_open_claim:
+/// locks a claim that's still pending or verified so it's only reviewed once
+async fn find_open_claim(
+    conn: &mut PgConnection,
+    claim_id: Uuid,
+) -> Result<StoreClaimModel, (StatusCode, Json<serde_json::Value>)> {
+    let claim = sqlx::query_as!(
+        StoreClaimModel,
+        "SELECT * FROM store_claims WHERE claim_id = $1 FOR UPDATE",
+        claim_id
+    )
+    .fetch_optional(conn)
+    .await
+    .map_err(internal_error)?;
+
+    match claim {
+        Some(claim) if matches!(claim.claim_status.as_str(), "pending" | "verified") => Ok(claim),
+        Some(claim) => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("claim_id {} was already {}", claim_id, claim.claim_status)
+            });
+            Err((StatusCode::CONFLICT, Json(error_response)))
+        }
+        None => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("claim_id {} not found", claim_id)
+            });
+            Err((StatusCode::NOT_FOUND, Json(error_response)))
+        }
+    }
+}
+
+/// notify_claimant:
+/// tells a claimant how their claim on a store was settled
+async fn notify_claimant(db: &Pool<Postgres>, claim: &StoreClaimModel) {
+    let store_name = sqlx::query_scalar!(
+        "SELECT store_name FROM record_stores WHERE record_store_id = $1",
+        claim.record_store_id
+    )
+    .fetch_optional(db)
+    .await
+    .ok()
+    .flatten()
+    .unwrap_or_else(|| "a record store".to_string());
+
+    let mut message = format!("Your claim on {} was {}", store_name, claim.claim_status);
+    if let Some(rejection_reason) = &claim.rejection_reason {
+        message.push_str(&format!(": {}", rejection_reason));
+    }
+
+    notify(
+        db,
+        claim.user_id,
+        "store_claim",
+        &message,
+        claim.reviewer_id,
+        Some(claim.claim_id),
+    )
+    .await;
+}
+
+/// POST claim a record store, the claim waits for an admin to verify it
+pub async fn claim_record_store(
+    Path(record_store_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<CreateStoreClaimSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    if body.evidence.trim().is_empty() {
+        let error_response = json!({
+            "status": "fail",
+            "message": "evidence can't be empty, tell us how we can check you run the store"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    let claimant = find_user(&data.db, body.user_id).await?;
+    check_store_exists(&data.db, record_store_id).await?;
+
+    let insert_result = sqlx::query_as!(
+        StoreClaimModel,
+        "INSERT INTO store_claims (record_store_id, user_id, evidence)
+        VALUES ($1, $2, $3) RETURNING *",
+        record_store_id,
+        body.user_id,
+        body.evidence.trim()
+    )
+    .fetch_one(&data.db)
+    .await;
+
+    match insert_result {
+        Ok(claim) => {
+            println!(
+                "POST: {} claimed record store {}",
+                claimant.user_name, record_store_id
+            );
+
+            Ok((
+                StatusCode::CREATED,
+                Json(json!({
+                    "status": "success",
+                    "claim": claim,
+                })),
+            ))
+        }
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => {
+            let error_response = json!({
+                "status": "fail",
+                "message": format!("{} already has an open claim on this store", claimant.user_name)
+            });
+            Err((StatusCode::CONFLICT, Json(error_response)))
+        }
+        Err(e) => Err(internal_error(e)),
+    }
+}
+
+/// GET a store's verified owners
+pub async fn get_store_owners(
+    Path(record_store_id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    check_store_exists(&data.db, record_store_id).await?;
+
+    let owners = sqlx::query_as!(
+        StoreOwnerModel,
+        "SELECT c.user_id, u.user_name, c.reviewed_at AS verified_at
+        FROM store_claims c JOIN users u USING (user_id)
+        WHERE c.record_store_id = $1 AND c.claim_status = 'verified'
+        ORDER BY c.reviewed_at",
+        record_store_id
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} owners of record store {}",
+        owners.len(),
+        record_store_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": owners.len(),
+        "owners": owners,
+    })))
+}
+
+/// GET the store claim queue
+/// defaults to pending claims, oldest first
+pub async fn list_store_claims(
+    Query(opts): Query<ClaimFilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_admin(&data.db, opts.admin_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let claims = sqlx::query_as!(
+        StoreClaimModel,
+        "SELECT * FROM store_claims WHERE claim_status = $1
+        ORDER BY created_at LIMIT $2 OFFSET $3",
+        opts.claim_status.as_deref().unwrap_or("pending"),
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!("GET: returning {} store claims", claims.len());
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": claims.len(),
+        "claims": claims,
+    })))
+}
+
+/// POST verify a pending store claim, making the claimant one of the store's owners
+pub async fn verify_store_claim(
+    Path(id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<VerifyStoreClaimSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_admin(&data.db, body.admin_id).await?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let claim = find_open_claim(&mut tx, id).await?;
+
+    if claim.claim_status == "verified" {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("claim_id {} was already verified", id)
+        });
+        return Err((StatusCode::CONFLICT, Json(error_response)));
+    }
+
+    let verified_claim = sqlx::query_as!(
+        StoreClaimModel,
+        "UPDATE store_claims SET claim_status = 'verified', reviewer_id = $1, reviewed_at = NOW()
+        WHERE claim_id = $2 RETURNING *",
+        body.admin_id,
+        id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    println!(
+        "POST: verified user {} as an owner of record store {}",
+        verified_claim.user_id, verified_claim.record_store_id
+    );
+
+    notify_claimant(&data.db, &verified_claim).await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "claim": verified_claim,
+    })))
+}
+
+/// POST reject a pending store claim, or revoke a verified one
+pub async fn reject_store_claim(
+    Path(id): Path<Uuid>,
+    State(data): State<Arc<AppState>>,
+    Json(body): Json<RejectStoreClaimSchema>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_admin(&data.db, body.admin_id).await?;
+
+    let mut tx = data.db.begin().await.map_err(internal_error)?;
+
+    let claim = find_open_claim(&mut tx, id).await?;
+
+    let claim_status = if claim.claim_status == "verified" {
+        "revoked"
+    } else {
+        "rejected"
+    };
+
+    let reviewed_claim = sqlx::query_as!(
+        StoreClaimModel,
+        "UPDATE store_claims SET claim_status = $1, reviewer_id = $2,
+        rejection_reason = $3, reviewed_at = NOW()
+        WHERE claim_id = $4 RETURNING *",
+        claim_status,
+        body.admin_id,
+        body.rejection_reason,
+        id
+    )
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal_error)?;
+
+    tx.commit().await.map_err(internal_error)?;
+
+    println!("POST: {} store claim {}", claim_status, id);
+
+    notify_claimant(&data.db, &reviewed_claim).await;
+
+    Ok(Json(json!({
+        "status": "success",
+        "claim": reviewed_claim,
+    })))
+}
+
+/// GET the store claims a user made and where they stand
+pub async fn get_user_store_claims(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<FilterOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    find_user(&data.db, user_id).await?;
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    let claims = sqlx::query_as!(
+        StoreClaimModel,
+        "SELECT * FROM store_claims WHERE user_id = $1
+        ORDER BY created_at DESC LIMIT $2 OFFSET $3",
+        user_id,
+        limit as i64,
+        offset as i64
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    println!(
+        "GET: returning {} store claims for user {}",
+        claims.len(),
+        user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": claims.len(),
+        "claims": claims,
+    })))
+}
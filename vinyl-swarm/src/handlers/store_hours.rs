@@ -0,0 +1,198 @@
+use axum::{http::StatusCode, Json};
+use serde_json::json;
+use sqlx::{PgConnection, PgExecutor};
+use uuid::Uuid;
+
+use crate::{
+    handlers::internal_error,
+    models::store::{HourExceptionModel, OpeningHoursModel, RecordStoreModel},
+};
+
+/// stores created without a time zone are assumed to be on eastern time,
+/// matching the record_stores.time_zone column default
+pub const DEFAULT_TIME_ZONE: &str = "America/New_York";
+
+/// check_time_zone:
+/// only IANA zone names postgres knows about can be used for a store
+pub async fn check_time_zone<'e>(
+    executor: impl PgExecutor<'e>,
+    time_zone: &str,
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let known_zone = sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM pg_timezone_names WHERE name = $1) AS "known!""#,
+        time_zone
+    )
+    .fetch_one(executor)
+    .await
+    .map_err(internal_error)?;
+
+    if !known_zone {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("unknown time_zone '{}', use a name like 'America/Chicago'", time_zone)
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    Ok(())
+}
+
+/// check_schedule:
+/// rejects days outside sunday (0) to saturday (6), exceptions with only one of
+/// opens_at and closes_at, and more than one exception for the same date
+pub fn check_schedule(
+    opening_hours: &[OpeningHoursModel],
+    hour_exceptions: &[HourExceptionModel],
+) -> Result<(), (StatusCode, Json<serde_json::Value>)> {
+    let fail = |message: String| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({"status": "fail", "message": message})),
+        )
+    };
+
+    if let Some(hours) = opening_hours
+        .iter()
+        .find(|hours| !(0..=6).contains(&hours.day_of_week))
+    {
+        return Err(fail(format!(
+            "day_of_week {} must be between 0 (sunday) and 6 (saturday)",
+            hours.day_of_week
+        )));
+    }
+
+    for (i, exception) in hour_exceptions.iter().enumerate() {
+        if exception.opens_at.is_some() != exception.closes_at.is_some() {
+            return Err(fail(format!(
+                "the exception on {} needs both opens_at and closes_at, or neither when closed",
+                exception.exception_date
+            )));
+        }
+        if hour_exceptions[..i]
+            .iter()
+            .any(|earlier| earlier.exception_date == exception.exception_date)
+        {
+            return Err(fail(format!(
+                "more than one exception on {}",
+                exception.exception_date
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// fetch_opening_hours:
+/// a store's weekly hours from sunday through saturday
+pub async fn fetch_opening_hours<'e>(
+    executor: impl PgExecutor<'e>,
+    record_store_id: Uuid,
+) -> Result<Vec<OpeningHoursModel>, sqlx::Error> {
+    sqlx::query_as!(
+        OpeningHoursModel,
+        "SELECT day_of_week, opens_at, closes_at FROM store_hours
+        WHERE record_store_id = $1 ORDER BY day_of_week, opens_at",
+        record_store_id
+    )
+    .fetch_all(executor)
+    .await
+}
+
+/// fetch_hour_exceptions:
+/// a store's exceptions in date order, upcoming_only leaves out the days
+/// already behind the store's local date
+pub async fn fetch_hour_exceptions<'e>(
+    executor: impl PgExecutor<'e>,
+    record_store_id: Uuid,
+    upcoming_only: bool,
+) -> Result<Vec<HourExceptionModel>, sqlx::Error> {
+    sqlx::query_as!(
+        HourExceptionModel,
+        "SELECT e.exception_date, e.opens_at, e.closes_at, e.note
+        FROM store_hour_exceptions e
+        JOIN record_stores s ON s.record_store_id = e.record_store_id
+        WHERE e.record_store_id = $1
+        AND (NOT $2 OR e.exception_date >= (NOW() AT TIME ZONE s.time_zone)::date)
+        ORDER BY e.exception_date",
+        record_store_id,
+        upcoming_only
+    )
+    .fetch_all(executor)
+    .await
+}
+
+/// replace_opening_hours:
+/// swaps a store's weekly hours for the supplied ones
+pub async fn replace_opening_hours(
+    conn: &mut PgConnection,
+    record_store_id: Uuid,
+    opening_hours: &[OpeningHoursModel],
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM store_hours WHERE record_store_id = $1",
+        record_store_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    for hours in opening_hours {
+        sqlx::query!(
+            "INSERT INTO store_hours (record_store_id, day_of_week, opens_at, closes_at)
+            VALUES ($1, $2, $3, $4) ON CONFLICT DO NOTHING",
+            record_store_id,
+            hours.day_of_week,
+            hours.opens_at,
+            hours.closes_at
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// replace_hour_exceptions:
+/// swaps a store's holiday and one-off hours for the supplied ones
+pub async fn replace_hour_exceptions(
+    conn: &mut PgConnection,
+    record_store_id: Uuid,
+    hour_exceptions: &[HourExceptionModel],
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        "DELETE FROM store_hour_exceptions WHERE record_store_id = $1",
+        record_store_id
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    for exception in hour_exceptions {
+        sqlx::query!(
+            "INSERT INTO store_hour_exceptions (record_store_id, exception_date, opens_at, closes_at, note)
+            VALUES ($1, $2, $3, $4, $5)",
+            record_store_id,
+            exception.exception_date,
+            exception.opens_at,
+            exception.closes_at,
+            exception.note
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// record_store_snapshot:
+/// the store along with its full schedule, as kept in the store's edit history
+pub async fn record_store_snapshot(
+    conn: &mut PgConnection,
+    record_store: &RecordStoreModel,
+) -> Result<serde_json::Value, sqlx::Error> {
+    let mut snapshot = json!(record_store);
+    snapshot["opening_hours"] =
+        json!(fetch_opening_hours(&mut *conn, record_store.record_store_id).await?);
+    snapshot["hour_exceptions"] =
+        json!(fetch_hour_exceptions(&mut *conn, record_store.record_store_id, false).await?);
+
+    Ok(snapshot)
+}
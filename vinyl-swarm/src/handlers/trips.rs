@@ -0,0 +1,205 @@
+use std::{collections::HashMap, ops::RangeInclusive, sync::Arc};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use chrono::{Duration, Utc};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    geocoder::great_circle_miles,
+    handlers::{internal_error, users::find_user},
+    models::{
+        store::RecordStoreModel,
+        trip::{DigTripOptions, DigTripStopModel},
+    },
+    route_planner::{plan_route, OpeningWindow, TripSettings, TripStore},
+    AppState,
+};
+
+/// a dig trip is a day out, stores that can't be reached within
+/// this many hours of setting off are left off the route
+const TRIP_HOURS: i64 = 12;
+
+/// how many of the favorite stores closest to the start get routed, 2-opt
+/// reschedules the whole route for every reversal so this keeps planning quick.
+/// stores further out are listed as missed
+const MAX_ROUTED_STORES: usize = 25;
+
+/// average travel speeds a trip can be planned at, from walking pace to the highway
+const SPEED_MPH_RANGE: RangeInclusive<f64> = 1.0..=100.0;
+
+/// GET a route through a user's favorite stores from a starting location,
+/// ordered to catch as many stores open as possible and finish early.
+/// only the MAX_ROUTED_STORES stores nearest the start are routed. stores that are
+/// closed or out of reach during the trip and stores without coordinates are
+/// listed separately
+pub async fn plan_dig_trip(
+    Path(user_id): Path<Uuid>,
+    Query(opts): Query<DigTripOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let start_at = opts.start_at.unwrap_or_else(Utc::now);
+    let Some(ends_at) = start_at.checked_add_signed(Duration::hours(TRIP_HOURS)) else {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("start_at leaves no room for a {} hour trip", TRIP_HOURS)
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    };
+
+    let settings = TripSettings {
+        start: (opts.lat, opts.lon),
+        start_at,
+        ends_at,
+        speed_mph: opts.speed_mph.unwrap_or(25.0),
+        stop_minutes: opts.stop_minutes.unwrap_or(45),
+    };
+
+    if !(-90.0..=90.0).contains(&opts.lat)
+        || !(-180.0..=180.0).contains(&opts.lon)
+        || !SPEED_MPH_RANGE.contains(&settings.speed_mph)
+        || !(0..=480).contains(&settings.stop_minutes)
+    {
+        let error_response = json!({
+            "status": "fail",
+            "message": "lat must be within -90 and 90, lon within -180 and 180, speed_mph between 1 and 100 and stop_minutes between 0 and 480"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    find_user(&data.db, user_id).await?;
+
+    let favorite_stores = sqlx::query_as::<_, RecordStoreModel>(
+        "SELECT s.* FROM record_stores s
+        JOIN user_record_stores f ON f.record_store_id = s.record_store_id
+        WHERE f.user_key = $1 ORDER BY s.store_name",
+    )
+    .bind(user_id)
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let (mut placed, unplaced): (Vec<_>, Vec<_>) = favorite_stores
+        .into_iter()
+        .partition(|store| store.latitude.is_some() && store.longitude.is_some());
+
+    placed.sort_by(|a, b| {
+        let from_start = |store: &RecordStoreModel| {
+            great_circle_miles(
+                settings.start,
+                (
+                    store.latitude.unwrap_or_default(),
+                    store.longitude.unwrap_or_default(),
+                ),
+            )
+        };
+        from_start(a).total_cmp(&from_start(b))
+    });
+    let out_of_range = placed.split_off(placed.len().min(MAX_ROUTED_STORES));
+
+    let store_ids: Vec<Uuid> = placed.iter().map(|store| store.record_store_id).collect();
+
+    let windows = sqlx::query!(
+        r#"SELECT s.record_store_id AS "record_store_id!",
+            w.window_opens AS "window_opens!", w.window_closes AS "window_closes!"
+        FROM UNNEST($1::uuid[]) AS s (record_store_id),
+            store_open_windows(s.record_store_id, $2, $3) AS w"#,
+        &store_ids,
+        settings.start_at,
+        settings.ends_at
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let mut store_windows: HashMap<Uuid, Vec<OpeningWindow>> = HashMap::new();
+    for window in windows {
+        store_windows
+            .entry(window.record_store_id)
+            .or_default()
+            .push((window.window_opens, window.window_closes));
+    }
+
+    // stores with a schedule on file, even one with nothing open during the trip
+    let stores_with_hours: Vec<Uuid> = sqlx::query_scalar!(
+        r#"SELECT record_store_id AS "record_store_id!" FROM store_hours WHERE record_store_id = ANY($1)
+        UNION SELECT record_store_id FROM store_hour_exceptions WHERE record_store_id = ANY($1)"#,
+        &store_ids
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(internal_error)?;
+
+    let trip_stores: Vec<TripStore> = placed
+        .iter()
+        .map(|store| TripStore {
+            coordinates: (
+                store.latitude.unwrap_or_default(),
+                store.longitude.unwrap_or_default(),
+            ),
+            windows: store_windows
+                .remove(&store.record_store_id)
+                .unwrap_or_default(),
+            hours_known: stores_with_hours.contains(&store.record_store_id),
+        })
+        .collect();
+
+    // route search is CPU bound, keep it off the async workers
+    let (plan, trip_stores) = tokio::task::spawn_blocking(move || {
+        let plan = plan_route(&trip_stores, &settings);
+        (plan, trip_stores)
+    })
+    .await
+    .map_err(|err| {
+        let error_response = json!({"status": "error", "message": format!("{:?}", err)});
+        (StatusCode::INTERNAL_SERVER_ERROR, Json(error_response))
+    })?;
+
+    let mut placed: Vec<Option<RecordStoreModel>> = placed.into_iter().map(Some).collect();
+
+    let route: Vec<DigTripStopModel> = plan
+        .stops
+        .iter()
+        .enumerate()
+        .filter_map(|(i, stop)| {
+            Some(DigTripStopModel {
+                stop: i + 1,
+                record_store: placed[stop.store].take()?,
+                leg_miles: (stop.leg_miles * 10.0).round() / 10.0,
+                arrives_at: stop.arrives_at,
+                wait_minutes: stop.wait_minutes,
+                departs_at: stop.departs_at,
+                hours_known: trip_stores[stop.store].hours_known,
+            })
+        })
+        .collect();
+
+    let missed_stores: Vec<RecordStoreModel> = plan
+        .missed
+        .iter()
+        .filter_map(|&store| placed[store].take())
+        .chain(out_of_range)
+        .collect();
+
+    println!(
+        "GET: planned a {} stop dig trip for user {}",
+        route.len(),
+        user_id
+    );
+
+    Ok(Json(json!({
+        "status": "success",
+        "start_at": settings.start_at,
+        "finishes_at": plan.finishes_at,
+        "total_miles": (plan.total_miles * 10.0).round() / 10.0,
+        "results": route.len(),
+        "route": route,
+        "missed_stores": missed_stores,
+        "stores_without_coordinates": unplaced,
+    })))
+}
@@ -6,21 +6,48 @@ use axum::{
 };
 use bcrypt::{hash, DEFAULT_COST};
 use bigdecimal::BigDecimal;
+use sqlx::{Pool, Postgres, QueryBuilder};
 use std::sync::Arc;
 
 use serde_json::json;
 use uuid::Uuid;
 
 use crate::{
-    handlers::records::combine_supplied_genres,
+    handlers::{
+        activity::record_activity,
+        genres::{find_genre, genre_with_subgenres},
+        internal_error,
+        records::combine_supplied_genres,
+    },
+    models::activity::ACTIVITY_VISIBILITIES,
     models::record::{CreateRecordSchema, RecordModel},
     models::user::{
-        CreateUserSchema, FilterOptions, PutUserRecord, UpdateUserSchema, UserModel,
-        UserResponseSchema,
+        CollectionRecordModel, CreateUserSchema, FilterOptions, PutUserRecord, UpdateUserSchema,
+        UserModel, UserProfileCardModel, UserResponseSchema, UserSearchOptions,
     },
 };
 use crate::{models::user::PatchUserRecord, AppState};
 
+/// find_user:
+/// the user acting on a request, 404 when the id doesn't belong to anyone
+pub async fn find_user(
+    db: &Pool<Postgres>,
+    user_id: Uuid,
+) -> Result<UserModel, (StatusCode, Json<serde_json::Value>)> {
+    let user_query = sqlx::query_as!(UserModel, "SELECT * FROM users WHERE user_id = $1", user_id)
+        .fetch_optional(db)
+        .await
+        .map_err(internal_error)?;
+
+    user_query.ok_or_else(|| {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("user_id {} not found", user_id)
+        });
+        (StatusCode::NOT_FOUND, Json(error_response))
+    })
+}
+
 pub async fn list_all_users(
     Query(opts): Query<FilterOptions>,
     State(data): State<Arc<AppState>>,
@@ -64,6 +91,157 @@ pub async fn list_all_users(
     }
 }
 
+/// like_prefix:
+/// a LIKE pattern matching text that starts with the search term, lowercased,
+/// with the term's own wildcards escaped
+fn like_prefix(term: &str) -> String {
+    let escaped = term
+        .trim()
+        .to_lowercase()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+
+    format!("{}%", escaped)
+}
+
+/// GET search for other collectors by name, city and the genres in their collections.
+/// only public profile cards come back, users blocked either way from the viewer
+/// and the viewer themselves are left out
+pub async fn search_users(
+    Query(opts): Query<UserSearchOptions>,
+    State(data): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, (StatusCode, Json<serde_json::Value>)> {
+    let shared_genres = opts.shared_genres.unwrap_or(false);
+
+    if shared_genres && opts.viewer_id.is_none() {
+        let error_response = json!({
+            "status": "fail",
+            "message": "shared_genres needs a viewer_id to compare collections with"
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
+    if let Some(viewer_id) = opts.viewer_id {
+        find_user(&data.db, viewer_id).await?;
+    }
+
+    let genre_names = match opts.genre.as_deref() {
+        Some(genre_name) => {
+            let genre = find_genre(&data.db, genre_name).await?;
+            Some(
+                genre_with_subgenres(&data.db, genre.genre_id)
+                    .await
+                    .map_err(internal_error)?,
+            )
+        }
+        None => None,
+    };
+
+    let limit = opts.limit.unwrap_or(10);
+    let offset = (opts.page.unwrap_or(1) - 1) * limit;
+
+    // genres are only counted for the viewer and for the users left once the
+    // filters below apply, never across every collection
+    let mut search_query = QueryBuilder::<Postgres>::new(
+        "WITH viewer_genres AS (
+            SELECT DISTINCT g.genre
+            FROM user_records ur JOIN records r USING (record_id)
+            CROSS JOIN unnest(r.genre) AS g (genre)
+            WHERE ur.user_id = ",
+    );
+    search_query.push_bind(opts.viewer_id).push(
+        ")
+        SELECT * FROM (
+            SELECT u.user_id, u.user_name, u.user_city, u.created_at,
+                (SELECT COUNT(*) FROM user_records ur WHERE ur.user_id = u.user_id) AS record_count,
+                genres.top_genres, genres.shared_genres
+            FROM users u
+            CROSS JOIN LATERAL (
+                SELECT COALESCE((array_agg(cg.genre ORDER BY cg.genre_count DESC, cg.genre))[1:3], '{}') AS top_genres,
+                    COALESCE(array_agg(cg.genre ORDER BY cg.genre)
+                        FILTER (WHERE cg.genre IN (SELECT genre FROM viewer_genres)), '{}') AS shared_genres
+                FROM (
+                    SELECT g.genre, COUNT(*) AS genre_count
+                    FROM user_records ur JOIN records r USING (record_id)
+                    CROSS JOIN unnest(r.genre) AS g (genre)
+                    WHERE ur.user_id = u.user_id
+                    GROUP BY g.genre
+                ) cg
+            ) genres
+            WHERE TRUE",
+    );
+
+    if let Some(q) = opts.q.as_deref().filter(|q| !q.trim().is_empty()) {
+        let pattern = like_prefix(q);
+        search_query
+            .push(" AND (lower(u.user_name) LIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR lower(u.user_first_name) LIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR lower(u.user_last_name) LIKE ")
+            .push_bind(pattern)
+            .push(")");
+    }
+    if let Some(city) = opts.city.as_deref().filter(|city| !city.trim().is_empty()) {
+        search_query
+            .push(" AND lower(u.user_city) = lower(")
+            .push_bind(city.trim().to_string())
+            .push(")");
+    }
+    if let Some(genre_names) = genre_names {
+        search_query
+            .push(
+                " AND EXISTS (SELECT 1 FROM user_records ur JOIN records r USING (record_id)
+                WHERE ur.user_id = u.user_id AND r.genre && ",
+            )
+            .push_bind(genre_names)
+            .push(")");
+    }
+    if let Some(viewer_id) = opts.viewer_id {
+        search_query
+            .push(" AND u.user_id <> ")
+            .push_bind(viewer_id)
+            .push(
+                " AND NOT EXISTS (SELECT 1 FROM user_blocks b
+                WHERE (b.blocker_id = u.user_id AND b.blocked_id = ",
+            )
+            .push_bind(viewer_id)
+            .push(") OR (b.blocker_id = ")
+            .push_bind(viewer_id)
+            .push(" AND b.blocked_id = u.user_id))");
+    }
+
+    search_query.push(") cards");
+    if shared_genres {
+        search_query.push(
+            " WHERE cardinality(shared_genres) > 0
+            ORDER BY cardinality(shared_genres) DESC, user_name",
+        );
+    } else {
+        search_query.push(" ORDER BY user_name");
+    }
+    search_query
+        .push(" LIMIT ")
+        .push_bind(limit as i64)
+        .push(" OFFSET ")
+        .push_bind(offset as i64);
+
+    let users = search_query
+        .build_query_as::<UserProfileCardModel>()
+        .fetch_all(&data.db)
+        .await
+        .map_err(internal_error)?;
+
+    println!("GET: returning {} users from a search", users.len());
+
+    Ok(Json(json!({
+        "status": "success",
+        "results": users.len(),
+        "users": users,
+    })))
+}
+
 pub async fn find_specific_user(
     Path(id): Path<Uuid>,
     State(data): State<Arc<AppState>>,
@@ -107,12 +285,13 @@ pub async fn create_user(
     //create the insertion query into postgres
     let query_result = sqlx::query_as!(
         UserModel,
-        "INSERT INTO users (user_name, user_first_name, user_last_name, user_email, user_password) VALUES ($1, $2, $3, $4, $5) RETURNING *",
+        "INSERT INTO users (user_name, user_first_name, user_last_name, user_email, user_password, user_city) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *",
         body.user_name.to_string(),
         body.user_first_name.to_string(),
         body.user_last_name.to_string(),
         body.user_email.to_string(),
-        create_hashed_password(body.user_password)
+        create_hashed_password(body.user_password),
+        body.user_city.as_deref().map(str::trim)
     )
     .fetch_one(&data.db)
     .await;
@@ -169,21 +348,80 @@ pub async fn edit_user(
         return Err((StatusCode::NOT_FOUND, Json(error_response)));
     }
 
+    if body
+        .activity_visibility
+        .as_deref()
+        .is_some_and(|visibility| !ACTIVITY_VISIBILITIES.contains(&visibility))
+    {
+        let error_response = json!({
+            "status": "fail",
+            "message": format!("activity_visibility must be one of {}", ACTIVITY_VISIBILITIES.join(", "))
+        });
+        return Err((StatusCode::BAD_REQUEST, Json(error_response)));
+    }
+
     // assume it can be modified from the body elements provided
     let user = query_result.unwrap();
 
-    let query_result = sqlx::query_as!(
-        UserModel,
-        "UPDATE users SET user_name = $1, user_first_name = $2, user_last_name = $3, user_email = $4, user_password = $5 WHERE user_id = $6 RETURNING *",
-        body.user_name.to_owned().unwrap_or(user.user_name),
-        body.user_first_name.to_owned().unwrap_or(user.user_first_name),
-        body.user_last_name.to_owned().unwrap_or(user.user_last_name),
-        body.user_email.to_owned().unwrap_or(user.user_email),
-        create_hashed_password(body.user_password.to_owned().unwrap_or(user.user_password)),
-        id,
-    )
-    .fetch_one(&data.db)
-    .await;
+    let query_result = if body.is_empty() {
+        Ok(user)
+    } else {
+        // only the supplied columns are updated, the stored password hash is
+        // left alone unless a new password is supplied
+        let mut update_query = QueryBuilder::<Postgres>::new("UPDATE users SET ");
+        let mut columns = update_query.separated(", ");
+
+        if let Some(user_name) = body.user_name {
+            columns
+                .push("user_name = ")
+                .push_bind_unseparated(user_name);
+        }
+        if let Some(user_first_name) = body.user_first_name {
+            columns
+                .push("user_first_name = ")
+                .push_bind_unseparated(user_first_name);
+        }
+        if let Some(user_last_name) = body.user_last_name {
+            columns
+                .push("user_last_name = ")
+                .push_bind_unseparated(user_last_name);
+        }
+        if let Some(user_email) = body.user_email {
+            columns
+                .push("user_email = ")
+                .push_bind_unseparated(user_email);
+        }
+        if let Some(user_password) = body.user_password {
+            columns
+                .push("user_password = ")
+                .push_bind_unseparated(create_hashed_password(user_password));
+        }
+        if let Some(alert_favorite_stores_only) = body.alert_favorite_stores_only {
+            columns
+                .push("alert_favorite_stores_only = ")
+                .push_bind_unseparated(alert_favorite_stores_only);
+        }
+        if let Some(activity_visibility) = body.activity_visibility {
+            columns
+                .push("activity_visibility = ")
+                .push_bind_unseparated(activity_visibility);
+        }
+        if let Some(user_city) = body.user_city {
+            columns
+                .push("user_city = ")
+                .push_bind_unseparated(user_city.as_deref().map(str::trim).map(String::from));
+        }
+
+        update_query
+            .push(" WHERE user_id = ")
+            .push_bind(id)
+            .push(" RETURNING *");
+
+        update_query
+            .build_query_as::<UserModel>()
+            .fetch_one(&data.db)
+            .await
+    };
 
     // respond accordingly
     match query_result {
@@ -238,11 +476,14 @@ pub async fn get_user_records(
     }
 
     // query for those sweet tunes you've collected
-    let record_query = sqlx::query_as!(
-        RecordModel,
-        "SELECT * FROM records WHERE record_id = ANY($1)",
-        &user_records_query,
+    let record_query = sqlx::query_as::<_, CollectionRecordModel>(
+        "SELECT r.*, ur.added_at, EXISTS (
+            SELECT 1 FROM record_loans l
+            WHERE l.user_record_id = ur.user_record_id AND l.returned_date IS NULL
+        ) AS on_loan
+        FROM user_records ur JOIN records r USING (record_id) WHERE ur.user_id = $1",
     )
+    .bind(user_id)
     .fetch_all(&data.db)
     .await;
 
@@ -288,6 +529,11 @@ pub async fn create_user_record(
     match user_query_check {
         // yay! found a user! let's add some sweet music
         Ok(found_user) => {
+            let genres = match combine_supplied_genres(&data.db, body.genre).await {
+                Ok(genres) => genres,
+                Err(e) => return internal_error(e),
+            };
+
             // query for the new record insertion
             let create_record_result = sqlx::query_as!(
                 RecordModel,
@@ -296,7 +542,7 @@ pub async fn create_user_record(
                 body.artist,
                 body.title,
                 body.released,
-                &combine_supplied_genres(body.genre),
+                &genres,
                 // unwrap if not supplied
                 body.format.as_deref().unwrap_or("LP"),
                 // if not supplied create empty value
@@ -322,6 +568,16 @@ pub async fn create_user_record(
 
                     match user_records_insert_query {
                         Ok(created_user_record) => {
+                            record_activity(
+                                &data.db,
+                                created_user_record.user_id,
+                                "collected",
+                                Some(created_record.record_id),
+                                None,
+                                None,
+                            )
+                            .await;
+
                             let create_user_record_resp = serde_json::json!({
                                 "status": "success",
                                 "records_collected": "1",
@@ -400,8 +656,9 @@ pub async fn put_user_record(
                 Ok(created_record) => {
                     // check to confirm
                     if let Ok(Some(_)) = sqlx::query!(
-                        "SELECT * FROM user_records WHERE record_id = $1",
-                        body.record_id
+                        "SELECT * FROM user_records WHERE record_id = $1 AND user_id = $2",
+                        body.record_id,
+                        found_user.user_id
                     )
                     .fetch_optional(&data.db)
                     .await
@@ -428,6 +685,16 @@ pub async fn put_user_record(
 
                     match user_records_insert_query {
                         Ok(created_user_record) => {
+                            record_activity(
+                                &data.db,
+                                created_user_record.user_id,
+                                "collected",
+                                Some(created_record.record_id),
+                                None,
+                                None,
+                            )
+                            .await;
+
                             let create_user_record_resp = serde_json::json!({
                                 "status": "success",
                                 "records_collected": "1",
@@ -6,8 +6,11 @@ use std::error::Error;
 use std::sync::Arc;
 
 // import routes module
+mod calendar;
+mod geocoder;
 mod handlers;
 mod models;
+mod route_planner;
 mod routes;
 
 pub struct AppState {
@@ -45,6 +48,8 @@ async fn main() {
     match connect_to_database().await {
         Ok(pool) => {
             let app_state = Arc::new(AppState { db: pool.clone() });
+            // keep the record similarity behind recommendations fresh
+            tokio::spawn(handlers::recommendations::similarity_job(pool.clone()));
             // create the app
             let app = routes::create_router(app_state);
 
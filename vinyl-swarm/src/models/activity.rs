@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{record::RecordModel, store::RecordStoreModel};
+
+/// who can see a user's activity: anyone, their followers, or nobody else
+pub const ACTIVITY_VISIBILITIES: [&str; 3] = ["public", "followers", "private"];
+
+/// query parameters for a user's feed of the people they follow
+#[derive(Deserialize, Debug)]
+pub struct FeedOptions {
+    pub user_id: Uuid,
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// query parameters for a user's own activity, viewer_id is whoever is looking
+#[derive(Deserialize, Debug)]
+pub struct ActivityOptions {
+    pub viewer_id: Option<Uuid>,
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// for paging through followers and the people a user follows
+#[derive(Deserialize, Debug, Default)]
+pub struct FilterOptions {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FollowUserSchema {
+    pub followed_id: Uuid,
+}
+
+/// something a user did, along with the record or store it was about
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ActivityModel {
+    pub activity_id: Uuid,
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub activity_type: String,
+    pub review_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    #[sqlx(json)]
+    pub record: Option<RecordModel>,
+    #[sqlx(json)]
+    pub record_store: Option<RecordStoreModel>,
+}
+
+/// a follower, or someone being followed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FollowModel {
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub followed_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::BigDecimal;
+use uuid::Uuid;
+
+use crate::models::{record::RecordModel, store::RecordStoreModel};
+
+/// for paging through a user's wishlist alerts, newest first
+#[derive(Deserialize, Debug, Default)]
+pub struct AlertFilterOptions {
+    pub unread_only: Option<bool>,
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// marks the listed alerts as read, or all of them without alert_ids
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MarkAlertsReadSchema {
+    pub alert_ids: Option<Vec<Uuid>>,
+}
+
+/// a wishlist record listed at a store, price is what it was listed for
+/// when the alert went out and quantity is what the store has left now
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WishlistAlertModel {
+    pub alert_id: Uuid,
+    pub listing_id: Uuid,
+    pub price: BigDecimal,
+    pub condition: String,
+    pub quantity: i32,
+    pub created_at: Option<DateTime<Utc>>,
+    pub read_at: Option<DateTime<Utc>>,
+    #[sqlx(json)]
+    pub record: RecordModel,
+    #[sqlx(json)]
+    pub record_store: RecordStoreModel,
+}
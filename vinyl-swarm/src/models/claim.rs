@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// a user claiming a store, evidence is whatever lets an admin check
+/// they run it, like a business email address or the shop's phone number
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateStoreClaimSchema {
+    pub user_id: Uuid,
+    pub evidence: String,
+}
+
+/// query parameters for the store claim queue, defaults to pending claims
+#[derive(Deserialize, Debug)]
+pub struct ClaimFilterOptions {
+    pub admin_id: Uuid,
+    pub claim_status: Option<String>,
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// for paging through a user's own claims
+#[derive(Deserialize, Debug, Default)]
+pub struct FilterOptions {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VerifyStoreClaimSchema {
+    pub admin_id: Uuid,
+}
+
+/// rejects a pending claim, or revokes a verified one, with a reason the claimant can read
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RejectStoreClaimSchema {
+    pub admin_id: Uuid,
+    pub rejection_reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct StoreClaimModel {
+    pub claim_id: Uuid,
+    pub record_store_id: Uuid,
+    pub user_id: Uuid,
+    pub evidence: String,
+    pub claim_status: String,
+    pub reviewer_id: Option<Uuid>,
+    pub rejection_reason: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+/// a verified owner of a store, as shown publicly
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StoreOwnerModel {
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub verified_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// why a comment is being reported
+pub const REPORT_REASONS: [&str; 5] = ["spam", "harassment", "hate", "off_topic", "other"];
+
+/// the longest a comment can be, in characters
+pub const COMMENT_MAX_CHARS: usize = 2000;
+
+/// for paging through the top level comments on something, newest first.
+/// each comes with all of its replies
+#[derive(Deserialize, Debug, Default)]
+pub struct FilterOptions {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// a new comment, parent_comment_id makes it a reply
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateCommentSchema {
+    pub user_id: Uuid,
+    pub body: String,
+    pub parent_comment_id: Option<Uuid>,
+}
+
+/// only the author can edit a comment
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateCommentSchema {
+    pub user_id: Uuid,
+    pub body: String,
+}
+
+/// only the author can delete a comment, moderators remove them instead
+#[derive(Deserialize, Debug)]
+pub struct DeleteCommentOptions {
+    pub user_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RemoveCommentSchema {
+    pub moderator_id: Uuid,
+    pub removal_reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReportCommentSchema {
+    pub user_id: Uuid,
+    pub reason: String,
+    pub details: Option<String>,
+}
+
+/// query parameters for the comment report queue, defaults to pending reports
+#[derive(Deserialize, Debug)]
+pub struct ReportFilterOptions {
+    pub admin_id: Uuid,
+    pub report_status: Option<String>,
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// an admin settling a report, "dismiss" keeps the comment and "remove" takes it
+/// down, settling every other pending report on it too
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResolveReportSchema {
+    pub admin_id: Uuid,
+    pub action: String,
+    pub removal_reason: Option<String>,
+}
+
+/// a comment and its replies. comment_status is "visible", "deleted" or "removed",
+/// the body of a deleted or removed comment isn't shown
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CommentModel {
+    pub comment_id: Uuid,
+    pub parent_comment_id: Option<Uuid>,
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub body: Option<String>,
+    pub comment_status: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub replies: Vec<CommentModel>,
+}
+
+/// a report in the admin queue along with the reported comment as written.
+/// pending_reports counts everyone's pending reports on the comment
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommentReportModel {
+    pub report_id: Uuid,
+    pub comment_id: Uuid,
+    pub reporter_id: Uuid,
+    pub reporter_name: String,
+    pub reason: String,
+    pub details: Option<String>,
+    pub report_status: String,
+    pub reviewer_id: Option<Uuid>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub comment_author_id: Uuid,
+    pub comment_author_name: String,
+    pub comment_body: String,
+    pub comment_removed_at: Option<DateTime<Utc>>,
+    pub pending_reports: i64,
+}
+
+/// a comment as stored, for the checks made before changing it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StoredCommentModel {
+    pub comment_id: Uuid,
+    pub user_id: Uuid,
+    pub record_id: Option<Uuid>,
+    pub record_store_id: Option<Uuid>,
+    pub collection_user_id: Option<Uuid>,
+    pub parent_comment_id: Option<Uuid>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub removed_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{patch::deserialize_some, record::RecordModel, store::RecordStoreModel};
+
+/// the kinds of events a store can put on
+pub const EVENT_TYPES: [&str; 5] = [
+    "record_store_day",
+    "in_store",
+    "swap_meet",
+    "listening_party",
+    "other",
+];
+
+/// for paging through upcoming events, soonest first
+#[derive(Deserialize, Debug, Default)]
+pub struct FilterOptions {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// a new event at a store. times take an offset, e.g. "2026-04-18T08:00:00-04:00",
+/// and record_ids ties records like RSD exclusives to the event
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateStoreEventSchema {
+    pub created_by: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub event_type: Option<String>,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub record_ids: Option<Vec<Uuid>>,
+}
+
+/// JSON Merge Patch body for an event, null clears the description and ends_at.
+/// record_ids replaces the event's records, [] clears them.
+/// only the event's creator, the store's owners or a moderator can change it
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateStoreEventSchema {
+    pub user_id: Uuid,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub title: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub event_type: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub starts_at: Option<DateTime<Utc>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub ends_at: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub record_ids: Option<Vec<Uuid>>,
+}
+
+/// events can be removed by their creator, the store's owners or a moderator
+#[derive(Deserialize, Debug)]
+pub struct DeleteEventOptions {
+    pub user_id: Uuid,
+}
+
+/// an event along with the records tied to it
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct StoreEventModel {
+    pub event_id: Uuid,
+    pub record_store_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub event_type: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    #[sqlx(json)]
+    pub records: Vec<RecordModel>,
+}
+
+/// an event along with the store putting it on, for feeds across several stores
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CalendarEventModel {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub event: StoreEventModel,
+    #[sqlx(json)]
+    pub record_store: RecordStoreModel,
+}
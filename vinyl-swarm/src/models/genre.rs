@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// for pagination in a front end UI
+#[derive(Deserialize, Debug, Default)]
+pub struct FilterOptions {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// for the top rated records in a genre, records need min_reviews
+/// reviews (default 1) before they're ranked
+#[derive(Deserialize, Debug, Default)]
+pub struct TopRatedOptions {
+    pub min_reviews: Option<i32>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateGenreSchema {
+    pub moderator_id: Uuid,
+    pub genre_name: String,
+    pub parent_genre: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateGenreAliasSchema {
+    pub moderator_id: Uuid,
+    pub alias: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GenreModel {
+    pub genre_id: Uuid,
+    pub genre_name: String,
+    pub parent_genre_id: Option<Uuid>,
+    pub aliases: Vec<String>,
+}
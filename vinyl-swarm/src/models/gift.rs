@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::record::RecordModel;
+
+/// a user's wishlist share link, path is where visitors open it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WishlistShareModel {
+    pub share_token: String,
+    pub path: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// a visitor reserving a record on a shared wishlist, no account needed
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReserveGiftSchema {
+    pub record_id: Uuid,
+    pub reserver_name: String,
+    pub note: Option<String>,
+}
+
+/// a record on a shared wishlist that nobody has reserved yet.
+/// target prices stay private to the owner
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct SharedWishlistRecordModel {
+    pub added_at: Option<DateTime<Utc>>,
+    #[sqlx(json)]
+    pub record: RecordModel,
+}
+
+/// a reservation as shown to the visitor who made it. cancel_token is only
+/// handed out here, it's what lets them cancel later
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GiftReservationModel {
+    pub reservation_id: Uuid,
+    pub record_id: Uuid,
+    pub reserver_name: String,
+    pub note: Option<String>,
+    pub cancel_token: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,78 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::BigDecimal;
+use uuid::Uuid;
+
+use crate::models::{patch::deserialize_some, record::RecordModel, store::RecordStoreModel};
+
+/// goldmine grades from mint down to poor
+pub const LISTING_CONDITIONS: [&str; 8] = ["M", "NM", "VG+", "VG", "G+", "G", "F", "P"];
+
+/// for paging through a store's stock
+#[derive(Deserialize, Debug, Default)]
+pub struct FilterOptions {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// a store listing, or a user reporting a copy they spotted in the bins.
+/// listing the same record and condition again updates the existing listing
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateListingSchema {
+    pub reported_by: Uuid,
+    pub record_id: Uuid,
+    pub price: BigDecimal,
+    pub condition: String,
+    pub quantity: Option<i32>,
+}
+
+/// JSON Merge Patch body for a listing, a quantity of 0 marks it sold out
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateListingSchema {
+    pub reported_by: Uuid,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub price: Option<BigDecimal>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub condition: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub quantity: Option<i32>,
+}
+
+/// the user removing a listing, needed once a store has verified owners
+#[derive(Deserialize, Debug, Default)]
+pub struct DeleteListingOptions {
+    pub user_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct InventoryListingModel {
+    pub listing_id: Uuid,
+    pub record_store_id: Uuid,
+    pub record_id: Uuid,
+    pub price: BigDecimal,
+    pub condition: String,
+    pub quantity: i32,
+    pub reported_by: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// a listing in a store's stock along with the record for sale
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct StoreStockModel {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub listing: InventoryListingModel,
+    #[sqlx(json)]
+    pub record: RecordModel,
+}
+
+/// a listing for a record along with the store selling it
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RecordAvailabilityModel {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub listing: InventoryListingModel,
+    #[sqlx(json)]
+    pub record_store: RecordStoreModel,
+}
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{patch::deserialize_some, record::RecordModel};
+
+/// who can see a list: anyone, anyone with its list_id, or only its owner
+pub const LIST_VISIBILITIES: [&str; 3] = ["public", "unlisted", "private"];
+
+/// query parameters for a user's lists, viewer_id is whoever is looking.
+/// the owner sees all their lists, everyone else only the public ones
+#[derive(Deserialize, Debug)]
+pub struct ListFilterOptions {
+    pub viewer_id: Option<Uuid>,
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// query parameters for one list, viewer_id is whoever is looking
+#[derive(Deserialize, Debug)]
+pub struct ViewListOptions {
+    pub viewer_id: Option<Uuid>,
+}
+
+/// a new list, private unless visibility says otherwise
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateRecordListSchema {
+    pub title: String,
+    pub description: Option<String>,
+    pub visibility: Option<String>,
+}
+
+/// JSON Merge Patch body for a list, null clears the description.
+/// only the list's owner can change it
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateRecordListSchema {
+    pub user_id: Uuid,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub title: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub description: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub visibility: Option<String>,
+}
+
+/// the list's owner, for the changes only they can make
+#[derive(Deserialize, Debug)]
+pub struct ListOwnerOptions {
+    pub user_id: Uuid,
+}
+
+/// adds a record to the end of a list, or at position pushing the rest down
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AddListRecordSchema {
+    pub user_id: Uuid,
+    pub record_id: Uuid,
+    pub note: Option<String>,
+    pub position: Option<i32>,
+}
+
+/// every record on the list in its new order
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReorderListSchema {
+    pub user_id: Uuid,
+    pub record_ids: Vec<Uuid>,
+}
+
+/// copies a list into user_id's lists, keeping the original title unless given one.
+/// the copy starts out private
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CopyRecordListSchema {
+    pub user_id: Uuid,
+    pub title: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordListModel {
+    pub list_id: Uuid,
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub visibility: String,
+    pub copied_from_list_id: Option<Uuid>,
+    pub record_count: i64,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// a record on a list, in its place
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ListRecordModel {
+    pub position: i32,
+    pub note: Option<String>,
+    pub added_at: Option<DateTime<Utc>>,
+    #[sqlx(json)]
+    pub record: RecordModel,
+}
@@ -0,0 +1,68 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{patch::deserialize_some, record::RecordModel};
+
+/// which of a user's loans to list: records still "out" (the default),
+/// ones that came back as "returned", or "all"
+pub const LOAN_STATUSES: [&str; 3] = ["out", "returned", "all"];
+
+/// query parameters for a user's loans
+#[derive(Deserialize, Debug, Default)]
+pub struct LoanFilterOptions {
+    pub status: Option<String>,
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// for paging through overdue loans
+#[derive(Deserialize, Debug, Default)]
+pub struct FilterOptions {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// lends a record from the user's collection, to either a user with an account
+/// (borrower_id) or anyone else by name (borrower_name). lent_date defaults to today
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateLoanSchema {
+    pub record_id: Uuid,
+    pub borrower_id: Option<Uuid>,
+    pub borrower_name: Option<String>,
+    pub lent_date: Option<NaiveDate>,
+    pub due_date: Option<NaiveDate>,
+    pub notes: Option<String>,
+}
+
+/// JSON Merge Patch body for a loan, null clears the due date and notes
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateLoanSchema {
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub due_date: Option<Option<NaiveDate>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub notes: Option<Option<String>>,
+}
+
+/// marks a loan returned, returned_date defaults to today
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReturnLoanSchema {
+    pub returned_date: Option<NaiveDate>,
+}
+
+/// a loan along with the record that was lent. overdue is true while the
+/// record is still out past its due date
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct LoanModel {
+    pub loan_id: Uuid,
+    pub borrower_id: Option<Uuid>,
+    pub borrower_name: String,
+    pub lent_date: NaiveDate,
+    pub due_date: Option<NaiveDate>,
+    pub returned_date: Option<NaiveDate>,
+    pub notes: Option<String>,
+    pub overdue: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    #[sqlx(json)]
+    pub record: RecordModel,
+}
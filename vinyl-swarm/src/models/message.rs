@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::record::RecordModel;
+
+/// for paging through conversations, messages and blocked users, newest first
+#[derive(Deserialize, Debug, Default)]
+pub struct FilterOptions {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// the first message to another user, picks up the existing thread if there is one.
+/// record_id attaches a record to the message
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StartConversationSchema {
+    pub recipient_id: Uuid,
+    pub body: String,
+    pub record_id: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SendMessageSchema {
+    pub body: String,
+    pub record_id: Option<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BlockUserSchema {
+    pub blocked_id: Uuid,
+}
+
+/// a thread as one of its participants sees it, user_id and user_name are the other
+/// participant and unread counts the messages they sent that haven't been read
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConversationModel {
+    pub conversation_id: Uuid,
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub last_message_at: Option<DateTime<Utc>>,
+    pub last_message: Option<String>,
+    pub unread: i64,
+}
+
+/// a message along with the record attached to it, if any
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DirectMessageModel {
+    pub message_id: Uuid,
+    pub conversation_id: Uuid,
+    pub sender_id: Uuid,
+    pub body: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub read_at: Option<DateTime<Utc>>,
+    #[sqlx(json)]
+    pub record: Option<RecordModel>,
+}
+
+/// a user someone has blocked
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlockModel {
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub blocked_at: Option<DateTime<Utc>>,
+}
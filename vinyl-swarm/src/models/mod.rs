@@ -1,3 +1,22 @@
+pub mod activity;
+pub mod alert;
+pub mod claim;
+pub mod comment;
+pub mod event;
+pub mod genre;
+pub mod gift;
+pub mod inventory;
+pub mod list;
+pub mod loan;
+pub mod message;
+pub mod moderation;
+pub mod notification;
+pub mod patch;
+pub mod play;
+pub mod recommendation;
 pub mod record;
+pub mod review;
+pub mod revision;
 pub mod store;
+pub mod trip;
 pub mod user;
@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// query parameters for the duplicate record finder
+#[derive(Deserialize, Debug)]
+pub struct DuplicateFilterOptions {
+    pub moderator_id: Uuid,
+    pub min_similarity: Option<f32>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MergeRecordsSchema {
+    pub moderator_id: Uuid,
+    pub surviving_record_id: Uuid,
+    pub duplicate_record_ids: Vec<Uuid>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DuplicateRecordPair {
+    pub record_id: Uuid,
+    pub duplicate_record_id: Uuid,
+    pub artist_similarity: f32,
+    pub title_similarity: f32,
+}
+
+/// query parameters for the suggested edit queue
+#[derive(Deserialize, Debug)]
+pub struct SuggestionFilterOptions {
+    pub moderator_id: Uuid,
+    pub suggestion_status: Option<String>,
+    pub entity_type: Option<String>,
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// for paging through a submitter's own suggestions
+#[derive(Deserialize, Debug, Default)]
+pub struct FilterOptions {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ApproveSuggestionSchema {
+    pub moderator_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RejectSuggestionSchema {
+    pub moderator_id: Uuid,
+    pub rejection_reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SuggestedEditModel {
+    pub suggestion_id: Uuid,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub submitter_id: Option<Uuid>,
+    // the submitted JSON Merge Patch body as is: omitted fields are absent
+    // and null clears the column when the suggestion is approved
+    pub changes: serde_json::Value,
+    pub suggestion_status: String,
+    pub reviewer_id: Option<Uuid>,
+    pub rejection_reason: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
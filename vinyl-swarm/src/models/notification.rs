@@ -0,0 +1,47 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// the kinds of notification the service sends, each one can be turned off
+pub const NOTIFICATION_TYPES: [&str; 5] = [
+    "new_follower",
+    "wishlist_match",
+    "store_claim",
+    "direct_message",
+    "comment",
+];
+
+/// for paging through a user's notifications, newest first
+#[derive(Deserialize, Debug, Default)]
+pub struct NotificationFilterOptions {
+    pub unread_only: Option<bool>,
+    pub notification_type: Option<String>,
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// marks the listed notifications as read, or all of them without notification_ids
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MarkNotificationsReadSchema {
+    pub notification_ids: Option<Vec<Uuid>>,
+}
+
+/// a notification in a user's inbox, actor_name is whoever caused it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationModel {
+    pub notification_id: Uuid,
+    pub notification_type: String,
+    pub message: String,
+    pub actor_id: Option<Uuid>,
+    pub actor_name: Option<String>,
+    pub subject_id: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+/// whether a user gets one type of notification
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NotificationPreferenceModel {
+    pub notification_type: String,
+    pub enabled: bool,
+}
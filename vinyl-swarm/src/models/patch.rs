@@ -0,0 +1,14 @@
+use serde::{Deserialize, Deserializer};
+
+/// deserialize_some:
+/// keeps JSON Merge Patch fields apart. a missing field falls back to `None` through
+/// `#[serde(default)]`, while anything present is wrapped in `Some`. for an
+/// `Option<Option<T>>` field an explicit null becomes `Some(None)` and clears the column,
+/// for an `Option<T>` field a null is rejected because the column can't be cleared.
+pub fn deserialize_some<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
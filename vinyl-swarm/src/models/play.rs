@@ -0,0 +1,72 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::record::RecordModel;
+
+/// how long a collection item can sit unplayed before it shows up as neglected
+pub const NEGLECTED_AFTER_DAYS: i32 = 365;
+
+/// the longest side label accepted, e.g. "A", "B2" or "Side 4"
+pub const SIDE_MAX_CHARS: usize = 10;
+
+/// for paging through a user's plays, newest first
+#[derive(Deserialize, Debug, Default)]
+pub struct FilterOptions {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// query parameters for play counts, order is "most" (the default) or "least".
+/// least played includes records that were never played
+#[derive(Deserialize, Debug, Default)]
+pub struct PlayCountOptions {
+    pub order: Option<String>,
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// a play of a record in the user's collection, played_at defaults to now and
+/// can't be in the future. a side leaves the rest of the record unplayed
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LogPlaySchema {
+    pub record_id: Uuid,
+    pub played_at: Option<DateTime<Utc>>,
+    pub side: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// one entry in a user's listening log
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PlayModel {
+    pub play_id: Uuid,
+    pub played_at: DateTime<Utc>,
+    pub side: Option<String>,
+    pub notes: Option<String>,
+    #[sqlx(json)]
+    pub record: RecordModel,
+}
+
+/// a collection item with how often and how recently it was played,
+/// last_played_at is null for records that were never played
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PlayCountModel {
+    pub play_count: i64,
+    pub last_played_at: Option<DateTime<Utc>>,
+    pub added_at: Option<DateTime<Utc>>,
+    #[sqlx(json)]
+    pub record: RecordModel,
+}
+
+/// total time spent listening, from each record's duration_length.
+/// side lengths aren't known, so a play of a single side counts as half of the
+/// record, which overcounts a side of a double or triple LP. estimated is true
+/// when side plays or records without a duration_length went into the totals
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListeningTimeModel {
+    pub total_plays: i64,
+    pub listening_seconds: i64,
+    pub plays_last_30_days: i64,
+    pub listening_seconds_last_30_days: i64,
+    pub estimated: bool,
+}
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::record::RecordModel;
+
+/// for paging through a user's recommendations, best first
+#[derive(Deserialize, Debug, Default)]
+pub struct FilterOptions {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RefreshSimilaritySchema {
+    pub admin_id: Uuid,
+}
+
+/// a record recommended to a user. source is "collaborative" when collectors of
+/// the user's records also own it, or "genre" when it only shares their genres.
+/// because_of are the user's records that led to it
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RecommendationModel {
+    pub source: String,
+    pub score: f64,
+    pub because_of: Vec<Uuid>,
+    #[sqlx(json)]
+    pub record: RecordModel,
+}
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::types::BigDecimal;
 use uuid::Uuid;
 
+use crate::models::patch::deserialize_some;
+
 #[derive(Deserialize, Debug, Default)]
 pub struct FilterOptions {
     pub page: Option<usize>,
@@ -24,19 +26,77 @@ pub struct CreateRecordSchema {
     pub duration_length: NaiveTime,
 }
 
+/// JSON Merge Patch body for a record:
+/// omitted fields are left alone, null clears genre, format and price
 #[derive(Serialize, Deserialize, Debug)]
 pub struct UpdateRecordSchema {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor_id: Option<Uuid>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub artist: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub title: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub released: Option<NaiveDate>,
-    pub genre: Option<Vec<String>>,
-    pub format: Option<String>,
-    pub price: Option<BigDecimal>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub genre: Option<Option<Vec<String>>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub format: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub price: Option<Option<BigDecimal>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub label: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub duration_length: Option<NaiveTime>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl UpdateRecordSchema {
+    /// true when the patch doesn't touch any column
+    pub fn is_empty(&self) -> bool {
+        self.artist.is_none()
+            && self.title.is_none()
+            && self.released.is_none()
+            && self.genre.is_none()
+            && self.format.is_none()
+            && self.price.is_none()
+            && self.label.is_none()
+            && self.duration_length.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct RecordModel {
     pub record_id: Uuid,
     pub artist: String,
@@ -47,4 +107,9 @@ pub struct RecordModel {
     pub price: Option<BigDecimal>,
     pub label: String,
     pub duration_length: NaiveTime,
+    // kept up to date from record_reviews, not editable
+    #[serde(default)]
+    pub average_rating: Option<f64>,
+    #[serde(default)]
+    pub review_count: i32,
 }
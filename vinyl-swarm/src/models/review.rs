@@ -0,0 +1,115 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::patch::deserialize_some;
+
+/// for paging through reviews, newest first
+#[derive(Deserialize, Debug, Default)]
+pub struct FilterOptions {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// a star rating from 1 to 5, with optional ratings for the store's
+/// selection, prices and staff
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateStoreReviewSchema {
+    pub user_id: Uuid,
+    pub rating: i16,
+    pub review_text: Option<String>,
+    pub visited_on: Option<NaiveDate>,
+    pub selection_rating: Option<i16>,
+    pub price_rating: Option<i16>,
+    pub staff_rating: Option<i16>,
+}
+
+/// JSON Merge Patch body for a store review, null clears everything but the rating.
+/// only the reviewer can change it
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateStoreReviewSchema {
+    pub user_id: Uuid,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub rating: Option<i16>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub review_text: Option<Option<String>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub visited_on: Option<Option<NaiveDate>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub selection_rating: Option<Option<i16>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub price_rating: Option<Option<i16>>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub staff_rating: Option<Option<i16>>,
+}
+
+/// reviews can be removed by the reviewer or a moderator
+#[derive(Deserialize, Debug)]
+pub struct DeleteReviewOptions {
+    pub user_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct StoreReviewModel {
+    pub review_id: Uuid,
+    pub record_store_id: Uuid,
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub rating: i16,
+    pub review_text: Option<String>,
+    pub visited_on: Option<NaiveDate>,
+    pub selection_rating: Option<i16>,
+    pub price_rating: Option<i16>,
+    pub staff_rating: Option<i16>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// average ratings across a store's reviews, per category
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StoreRatingSummary {
+    pub average_rating: Option<f64>,
+    pub average_selection_rating: Option<f64>,
+    pub average_price_rating: Option<f64>,
+    pub average_staff_rating: Option<f64>,
+    pub review_count: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateRecordReviewSchema {
+    pub user_id: Uuid,
+    pub rating: i16,
+    pub review_text: Option<String>,
+}
+
+/// JSON Merge Patch body for a record review, null clears the review text.
+/// only the reviewer can change it
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateRecordReviewSchema {
+    pub user_id: Uuid,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub rating: Option<i16>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub review_text: Option<Option<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RecordReviewModel {
+    pub review_id: Uuid,
+    pub record_id: Uuid,
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub rating: i16,
+    pub review_text: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// a record's average rating and how many reviews gave it each number of stars,
+/// star_counts runs from 1 star to 5
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordRatingSummary {
+    pub average_rating: Option<f64>,
+    pub review_count: i64,
+    pub star_counts: Vec<i64>,
+}
@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// for paging through long edit histories
+#[derive(Deserialize, Debug, Default)]
+pub struct FilterOptions {
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RevertRevisionSchema {
+    pub moderator_id: Uuid,
+    pub revision_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevisionModel {
+    pub revision_id: Uuid,
+    pub entity_type: String,
+    pub entity_id: Uuid,
+    pub revision_number: i32,
+    pub editor_id: Option<Uuid>,
+    pub editor_name: Option<String>,
+    pub snapshot: serde_json::Value,
+    pub diff: serde_json::Value,
+    pub reverted_to: Option<Uuid>,
+    pub created_at: Option<DateTime<Utc>>,
+}
@@ -1,11 +1,18 @@
+use chrono::{NaiveDate, NaiveTime};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-/// for pagination in a front end UI
+use crate::models::patch::deserialize_some;
+
+/// for pagination in a front end UI,
+/// open_now=true only returns stores open at the moment,
+/// sort=rating puts the best reviewed stores first
 #[derive(Deserialize, Debug, Default)]
 pub struct FilterOptions {
     pub page: Option<usize>,
     pub limit: Option<usize>,
+    pub open_now: Option<bool>,
+    pub sort: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -17,6 +24,21 @@ pub struct CreateRecordStoreSchema {
     pub store_zip: String,
     pub phone_number: Option<String>,
     pub website: Option<String>,
+    // resolved from store_zip when not supplied
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    // IANA name like "America/Chicago", defaults to eastern time
+    pub time_zone: Option<String>,
+}
+
+/// query parameters for finding stores around a location,
+/// radius is in miles and defaults to 25
+#[derive(Deserialize, Debug)]
+pub struct NearbyOptions {
+    pub lat: f64,
+    pub lon: f64,
+    pub radius: Option<f64>,
+    pub limit: Option<usize>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -29,18 +51,110 @@ pub struct PatchRecordStoreSchema {
     pub record_store_id: Uuid,
 }
 
+/// JSON Merge Patch body for a record store:
+/// omitted fields are left alone, null clears phone_number, website and the coordinates.
+/// a new store_zip without coordinates moves the store to the zip code's centroid.
+/// opening_hours and hour_exceptions replace the store's whole schedule, [] clears them
 #[derive(Serialize, Deserialize, Debug)]
 pub struct UpdateRecordStoreSchema {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor_id: Option<Uuid>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub store_name: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub store_address: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub store_city: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub store_state: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub store_zip: Option<String>,
-    pub phone_number: Option<String>,
-    pub website: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub phone_number: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub website: Option<Option<String>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub latitude: Option<Option<f64>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub longitude: Option<Option<f64>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub time_zone: Option<String>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub opening_hours: Option<Vec<OpeningHoursModel>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_some",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub hour_exceptions: Option<Vec<HourExceptionModel>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl UpdateRecordStoreSchema {
+    /// true when the patch changes a column of the record_stores row
+    pub fn changes_columns(&self) -> bool {
+        self.store_name.is_some()
+            || self.store_address.is_some()
+            || self.store_city.is_some()
+            || self.store_state.is_some()
+            || self.store_zip.is_some()
+            || self.phone_number.is_some()
+            || self.website.is_some()
+            || self.latitude.is_some()
+            || self.longitude.is_some()
+            || self.time_zone.is_some()
+    }
+
+    /// true when the patch doesn't touch any column or the opening hours
+    pub fn is_empty(&self) -> bool {
+        !self.changes_columns() && self.opening_hours.is_none() && self.hour_exceptions.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct RecordStoreModel {
     pub record_store_id: Uuid,
     pub store_name: String,
@@ -50,4 +164,40 @@ pub struct RecordStoreModel {
     pub store_zip: String,
     pub phone_number: Option<String>,
     pub website: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub time_zone: String,
+    // kept up to date from store_reviews, not editable
+    #[serde(default)]
+    pub average_rating: Option<f64>,
+    #[serde(default)]
+    pub review_count: i32,
+}
+
+/// a weekly opening window in the store's local time,
+/// day_of_week runs from 0 (sunday) to 6 (saturday)
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct OpeningHoursModel {
+    pub day_of_week: i16,
+    pub opens_at: NaiveTime,
+    pub closes_at: NaiveTime,
+}
+
+/// a holiday or one-off change replacing a day's weekly hours,
+/// leaving out opens_at and closes_at marks the store closed
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct HourExceptionModel {
+    pub exception_date: NaiveDate,
+    pub opens_at: Option<NaiveTime>,
+    pub closes_at: Option<NaiveTime>,
+    pub note: Option<String>,
+}
+
+/// a record store along with how far away it is
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct NearbyRecordStoreModel {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub record_store: RecordStoreModel,
+    pub distance_miles: f64,
 }
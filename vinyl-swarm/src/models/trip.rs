@@ -0,0 +1,30 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::store::RecordStoreModel;
+
+/// query parameters for planning a dig trip from a starting location.
+/// start_at defaults to now and the trip runs for up to 12 hours. speed_mph is the
+/// average door to door speed (default 25, between 1 and 100) and stop_minutes
+/// the time spent at each store (default 45)
+#[derive(Deserialize, Debug)]
+pub struct DigTripOptions {
+    pub lat: f64,
+    pub lon: f64,
+    pub start_at: Option<DateTime<Utc>>,
+    pub speed_mph: Option<f64>,
+    pub stop_minutes: Option<i64>,
+}
+
+/// a store on the route with the estimated times there,
+/// leg_miles is the straight line distance from the previous stop
+#[derive(Debug, Serialize)]
+pub struct DigTripStopModel {
+    pub stop: usize,
+    pub record_store: RecordStoreModel,
+    pub leg_miles: f64,
+    pub arrives_at: DateTime<Utc>,
+    pub wait_minutes: i64,
+    pub departs_at: DateTime<Utc>,
+    pub hours_known: bool,
+}
@@ -1,7 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::types::BigDecimal;
 use uuid::Uuid;
 
+use crate::models::{patch::deserialize_some, record::RecordModel};
+
 /// for pagination in a front end UI
 #[derive(Deserialize, Debug, Default)]
 pub struct FilterOptions {
@@ -9,6 +12,35 @@ pub struct FilterOptions {
     pub limit: Option<usize>,
 }
 
+/// query parameters for finding other collectors. q matches the start of a
+/// user name, first name or last name, genre matches collections holding the genre
+/// or any of its sub-genres, and shared_genres ranks users by how many of the
+/// viewer's collection genres they share, so it needs a viewer_id
+#[derive(Deserialize, Debug, Default)]
+pub struct UserSearchOptions {
+    pub q: Option<String>,
+    pub city: Option<String>,
+    pub genre: Option<String>,
+    pub viewer_id: Option<Uuid>,
+    pub shared_genres: Option<bool>,
+    pub page: Option<usize>,
+    pub limit: Option<usize>,
+}
+
+/// what anyone can see of a user, real names and emails stay private.
+/// top_genres are the three genres most of the collection falls under,
+/// shared_genres are the ones the collection has in common with the viewer's
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UserProfileCardModel {
+    pub user_id: Uuid,
+    pub user_name: String,
+    pub user_city: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub record_count: i64,
+    pub top_genres: Vec<String>,
+    pub shared_genres: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct PutUserRecord {
     pub record_id: Uuid,
@@ -19,6 +51,41 @@ pub struct PatchUserRecord {
     pub record_id: Uuid,
 }
 
+/// adds an existing record to a wishlist, alerts go out for listings at or
+/// under the target price, or at any price without one
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PutWishlistRecord {
+    pub record_id: Uuid,
+    pub target_price: Option<BigDecimal>,
+}
+
+/// JSON Merge Patch body for a wishlist entry, null clears the target price
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateWishlistRecordSchema {
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub target_price: Option<Option<BigDecimal>>,
+}
+
+/// a wished for record along with what the user is willing to pay
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WishlistRecordModel {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub record: RecordModel,
+    pub target_price: Option<BigDecimal>,
+    pub added_at: Option<DateTime<Utc>>,
+}
+
+/// a record in a user's collection, on_loan is true while it's lent to someone
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CollectionRecordModel {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub record: RecordModel,
+    pub added_at: Option<DateTime<Utc>>,
+    pub on_loan: bool,
+}
+
 // due to security concerns
 #[derive(Serialize, Deserialize, Debug)]
 pub struct UserResponseSchema {
@@ -27,7 +94,11 @@ pub struct UserResponseSchema {
     pub user_first_name: String,
     pub user_last_name: String,
     pub user_email: String,
+    pub user_role: String,
     pub created_at: Option<DateTime<Utc>>,
+    pub alert_favorite_stores_only: bool,
+    pub activity_visibility: String,
+    pub user_city: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -37,15 +108,44 @@ pub struct CreateUserSchema {
     pub user_last_name: String,
     pub user_email: String,
     pub user_password: String,
+    pub user_city: Option<String>,
 }
 
+/// JSON Merge Patch body for a user:
+/// omitted fields are left alone, only user_city can be cleared with null
 #[derive(Serialize, Deserialize, Debug)]
 pub struct UpdateUserSchema {
+    #[serde(default, deserialize_with = "deserialize_some")]
     pub user_name: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
     pub user_first_name: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
     pub user_last_name: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
     pub user_email: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
     pub user_password: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub alert_favorite_stores_only: Option<bool>,
+    // public, followers or private
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub activity_visibility: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_some")]
+    pub user_city: Option<Option<String>>,
+}
+
+impl UpdateUserSchema {
+    /// true when the patch doesn't touch any column
+    pub fn is_empty(&self) -> bool {
+        self.user_name.is_none()
+            && self.user_first_name.is_none()
+            && self.user_last_name.is_none()
+            && self.user_email.is_none()
+            && self.user_password.is_none()
+            && self.alert_favorite_stores_only.is_none()
+            && self.activity_visibility.is_none()
+            && self.user_city.is_none()
+    }
 }
 
 // helps for converting to the appropriate exposed
@@ -58,12 +158,16 @@ impl From<UserModel> for UserResponseSchema {
             user_first_name: user.user_first_name,
             user_last_name: user.user_last_name,
             user_email: user.user_email,
+            user_role: user.user_role,
             created_at: user.created_at,
+            alert_favorite_stores_only: user.alert_favorite_stores_only,
+            activity_visibility: user.activity_visibility,
+            user_city: user.user_city,
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct UserModel {
     pub user_id: Uuid,
     pub user_name: String,
@@ -72,4 +176,25 @@ pub struct UserModel {
     pub user_email: String,
     pub user_password: String,
     pub created_at: Option<DateTime<Utc>>,
+    pub user_role: String,
+    pub alert_favorite_stores_only: bool,
+    pub activity_visibility: String,
+    pub user_city: Option<String>,
+}
+
+impl UserModel {
+    /// trusted users edit the community catalog without review
+    pub fn is_trusted(&self) -> bool {
+        matches!(self.user_role.as_str(), "trusted" | "moderator" | "admin")
+    }
+
+    /// moderators and admins look after the community catalog
+    pub fn is_moderator(&self) -> bool {
+        matches!(self.user_role.as_str(), "moderator" | "admin")
+    }
+
+    /// only admins verify who owns a record store and work the comment report queue
+    pub fn is_admin(&self) -> bool {
+        self.user_role == "admin"
+    }
 }
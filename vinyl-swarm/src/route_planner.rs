@@ -0,0 +1,183 @@
+use chrono::{DateTime, Duration, Utc};
+
+use crate::geocoder::great_circle_miles;
+
+/// when a store opens and closes, as points in time
+pub type OpeningWindow = (DateTime<Utc>, DateTime<Utc>);
+
+/// a store to fit into a trip, windows are its opening times during the trip.
+/// stores without any hours on file are treated as open whenever we get there
+#[derive(Debug, Clone)]
+pub struct TripStore {
+    pub coordinates: (f64, f64),
+    pub windows: Vec<OpeningWindow>,
+    pub hours_known: bool,
+}
+
+/// a visit in the planned route, store is the index into the stores planned
+#[derive(Debug, Clone)]
+pub struct PlannedStop {
+    pub store: usize,
+    pub leg_miles: f64,
+    pub arrives_at: DateTime<Utc>,
+    pub wait_minutes: i64,
+    pub departs_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TripPlan {
+    pub stops: Vec<PlannedStop>,
+    // stores that are closed, or can't be reached before the trip ends
+    pub missed: Vec<usize>,
+    pub total_miles: f64,
+    pub finishes_at: DateTime<Utc>,
+}
+
+impl TripPlan {
+    /// routes are compared on stores missed, then finishing time, then distance
+    fn cost(&self) -> (usize, DateTime<Utc>, f64) {
+        (self.missed.len(), self.finishes_at, self.total_miles)
+    }
+
+    fn is_better_than(&self, other: &TripPlan) -> bool {
+        self.cost()
+            .partial_cmp(&other.cost())
+            .is_some_and(|ordering| ordering.is_lt())
+    }
+}
+
+/// the trip's settings: where and when it starts and has to be over by,
+/// the average travel speed and how long to browse the bins at each store
+#[derive(Debug, Clone, Copy)]
+pub struct TripSettings {
+    pub start: (f64, f64),
+    pub start_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub speed_mph: f64,
+    pub stop_minutes: i64,
+}
+
+/// visit_window:
+/// when a visit arriving at `arrives_at` can start and the latest it can end,
+/// waiting for the store to open if needed. None when the store has closed for
+/// the trip or won't open before it ends
+fn visit_window(
+    store: &TripStore,
+    arrives_at: DateTime<Utc>,
+    trip_ends_at: DateTime<Utc>,
+) -> Option<(DateTime<Utc>, Option<DateTime<Utc>>)> {
+    if !store.hours_known {
+        return (arrives_at < trip_ends_at).then_some((arrives_at, None));
+    }
+
+    store
+        .windows
+        .iter()
+        .find(|(_, closes)| *closes > arrives_at)
+        .map(|(opens, closes)| (arrives_at.max(*opens), Some(*closes)))
+        .filter(|(visit_starts, _)| *visit_starts < trip_ends_at)
+}
+
+/// schedule:
+/// walks the stores in the given order, estimating arrival and departure times.
+/// a store that's closed when reached, or only reached after the trip ends,
+/// is skipped and the trip carries on from the last store visited
+fn schedule(order: &[usize], stores: &[TripStore], settings: &TripSettings) -> TripPlan {
+    let mut position = settings.start;
+    let mut clock = settings.start_at;
+    let mut stops = Vec::new();
+    let mut missed = Vec::new();
+    let mut total_miles = 0.0;
+
+    for &store_index in order {
+        let store = &stores[store_index];
+        let leg_miles = great_circle_miles(position, store.coordinates);
+        let travel_seconds = (leg_miles / settings.speed_mph * 3600.0).round() as i64;
+
+        // a leg too long to put a time on can't be made before the trip ends either
+        let Some(arrives_at) = Duration::try_seconds(travel_seconds)
+            .and_then(|travel_time| clock.checked_add_signed(travel_time))
+        else {
+            missed.push(store_index);
+            continue;
+        };
+
+        let Some((visit_starts, closes)) = visit_window(store, arrives_at, settings.ends_at) else {
+            missed.push(store_index);
+            continue;
+        };
+
+        let mut departs_at = visit_starts + Duration::minutes(settings.stop_minutes);
+        if let Some(closes) = closes {
+            departs_at = departs_at.min(closes);
+        }
+
+        stops.push(PlannedStop {
+            store: store_index,
+            leg_miles,
+            arrives_at,
+            wait_minutes: (visit_starts - arrives_at).num_minutes(),
+            departs_at,
+        });
+        total_miles += leg_miles;
+        position = store.coordinates;
+        clock = departs_at;
+    }
+
+    TripPlan {
+        stops,
+        missed,
+        total_miles,
+        finishes_at: clock,
+    }
+}
+
+/// nearest_neighbour_order:
+/// a first route heading to whichever unvisited store is closest each time
+fn nearest_neighbour_order(start: (f64, f64), stores: &[TripStore]) -> Vec<usize> {
+    let mut remaining: Vec<usize> = (0..stores.len()).collect();
+    let mut order = Vec::with_capacity(stores.len());
+    let mut position = start;
+
+    while !remaining.is_empty() {
+        let (nearest, _) = remaining
+            .iter()
+            .enumerate()
+            .map(|(i, &store)| (i, great_circle_miles(position, stores[store].coordinates)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .expect("remaining is not empty");
+        let store = remaining.swap_remove(nearest);
+        position = stores[store].coordinates;
+        order.push(store);
+    }
+
+    order
+}
+
+/// plan_route:
+/// orders the stores into a one-way route from the start, beginning from the
+/// nearest neighbour route and improving it with 2-opt. a reversal is kept when it
+/// misses fewer stores, finishes sooner or covers less distance, in that order
+pub fn plan_route(stores: &[TripStore], settings: &TripSettings) -> TripPlan {
+    let mut order = nearest_neighbour_order(settings.start, stores);
+    let mut best = schedule(&order, stores, settings);
+
+    let mut improved = true;
+    while improved {
+        improved = false;
+        for i in 0..order.len().saturating_sub(1) {
+            for j in i + 1..order.len() {
+                order[i..=j].reverse();
+                let candidate = schedule(&order, stores, settings);
+                if candidate.is_better_than(&best) {
+                    best = candidate;
+                    improved = true;
+                } else {
+                    order[i..=j].reverse();
+                }
+            }
+        }
+    }
+
+    best
+}
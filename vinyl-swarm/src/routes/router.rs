@@ -1,8 +1,58 @@
-use axum::{response::IntoResponse, routing::get, Json, Router};
+use axum::{
+    response::IntoResponse,
+    routing::{delete, get, patch, post, put},
+    Json, Router,
+};
 use std::sync::Arc;
 
 // internal modules
 use crate::{
+    handlers::activity::{
+        follow_user, get_feed, get_followers, get_following, get_user_activity, unfollow_user,
+    },
+    handlers::alerts::{get_user_alerts, mark_alerts_read},
+    handlers::comments::{
+        create_collection_comment, create_record_comment, create_store_comment, delete_comment,
+        edit_comment, get_collection_comments, get_record_comments, get_store_comments,
+        list_comment_reports, remove_comment, report_comment, resolve_comment_report,
+    },
+    handlers::events::{
+        create_store_event, delete_store_event, edit_store_event, get_store_events,
+        get_store_events_ics, get_user_events, get_user_events_ics,
+    },
+    handlers::genres::{
+        add_genre_alias, create_genre, get_genre_records, get_top_rated_in_genre, list_genres,
+    },
+    handlers::gifts::{
+        cancel_gift_reservation, create_wishlist_share, get_shared_wishlist, get_wishlist_share,
+        reserve_gift, revoke_wishlist_share,
+    },
+    handlers::inventory::{
+        add_store_listing, delete_store_listing, edit_store_listing, get_record_availability,
+        get_store_inventory,
+    },
+    handlers::lists::{
+        add_list_record, copy_record_list, create_record_list, delete_record_list,
+        edit_record_list, get_record_list, get_user_lists, remove_list_record, reorder_record_list,
+    },
+    handlers::loans::{edit_loan, get_overdue_loans, get_user_loans, lend_record, return_loan},
+    handlers::messages::{
+        block_user, get_blocked_users, get_conversation_messages, get_conversations,
+        mark_conversation_read, send_message, start_conversation, unblock_user,
+    },
+    handlers::moderation::{
+        approve_suggestion, find_duplicate_records, get_user_suggestions, list_suggestions,
+        merge_records, reject_suggestion,
+    },
+    handlers::notifications::{
+        edit_notification_preferences, get_notification_preferences, get_user_notifications,
+        mark_notification_read, mark_notifications_read,
+    },
+    handlers::plays::{
+        delete_play, get_listening_time, get_neglected_records, get_play_counts, get_user_plays,
+        log_play,
+    },
+    handlers::recommendations::{get_user_recommendations, refresh_recommendations},
     handlers::record_stores::{
         add_existing_record_store,
         add_user_record_store,
@@ -10,6 +60,7 @@ use crate::{
         delete_record_store,
         delete_user_record_store,
         edit_record_store,
+        find_nearby_stores,
         find_record_store,
         // user_record_stores:
         get_user_record_stores,
@@ -20,6 +71,7 @@ use crate::{
         create_new_record,
         delete_record_by_id,
         edit_record,
+        edit_wishlist_record,
         find_record,
         // wishlists:
         get_users_wishlist,
@@ -28,10 +80,22 @@ use crate::{
         remove_user_wishlist,
         remove_wishlist_record,
     },
+    handlers::reviews::{
+        create_record_review, create_store_review, delete_record_review, delete_store_review,
+        edit_record_review, edit_store_review, get_record_reviews, get_store_reviews,
+    },
+    handlers::revisions::{
+        get_record_history, get_record_store_history, revert_record, revert_record_store,
+    },
+    handlers::store_claims::{
+        claim_record_store, get_store_owners, get_user_store_claims, list_store_claims,
+        reject_store_claim, verify_store_claim,
+    },
+    handlers::trips::plan_dig_trip,
     handlers::users::{
         create_user, create_user_record, delete_user, edit_user, find_specific_user,
         get_user_records, list_all_users, put_user_record, remove_all_user_records,
-        remove_user_record,
+        remove_user_record, search_users,
     },
     AppState,
 };
@@ -62,14 +126,60 @@ pub fn create_router(app_state: Arc<AppState>) -> Router {
                 .patch(edit_record)
                 .delete(delete_record_by_id),
         )
+        .route("/records/{id}/availability", get(get_record_availability))
+        .route(
+            "/records/{id}/reviews",
+            get(get_record_reviews).post(create_record_review),
+        )
+        .route(
+            "/records/{id}/reviews/{review_id}",
+            patch(edit_record_review).delete(delete_record_review),
+        )
+        .route("/records/{id}/history", get(get_record_history))
+        .route("/records/{id}/revert", post(revert_record))
         .route("/stores", get(list_all_stores).post(create_record_store))
+        .route("/stores/nearby", get(find_nearby_stores))
         .route(
             "/stores/{id}",
             get(find_record_store)
                 .patch(edit_record_store)
                 .delete(delete_record_store),
         )
+        .route(
+            "/stores/{id}/inventory",
+            get(get_store_inventory).post(add_store_listing),
+        )
+        .route(
+            "/stores/{id}/inventory/{listing_id}",
+            patch(edit_store_listing).delete(delete_store_listing),
+        )
+        .route(
+            "/stores/{id}/reviews",
+            get(get_store_reviews).post(create_store_review),
+        )
+        .route(
+            "/stores/{id}/reviews/{review_id}",
+            patch(edit_store_review).delete(delete_store_review),
+        )
+        .route(
+            "/stores/{id}/events",
+            get(get_store_events).post(create_store_event),
+        )
+        .route("/stores/{id}/events.ics", get(get_store_events_ics))
+        .route(
+            "/stores/{id}/events/{event_id}",
+            patch(edit_store_event).delete(delete_store_event),
+        )
+        .route("/stores/{id}/claims", post(claim_record_store))
+        .route("/stores/{id}/owners", get(get_store_owners))
+        .route("/stores/{id}/history", get(get_record_store_history))
+        .route("/stores/{id}/revert", post(revert_record_store))
+        .route("/genres", get(list_genres).post(create_genre))
+        .route("/genres/{name}/records", get(get_genre_records))
+        .route("/genres/{name}/top_rated", get(get_top_rated_in_genre))
+        .route("/genres/{name}/aliases", post(add_genre_alias))
         .route("/users", get(list_all_users).post(create_user))
+        .route("/users/search", get(search_users))
         .route(
             "/users/{id}",
             get(find_specific_user).patch(edit_user).delete(delete_user),
@@ -90,12 +200,151 @@ pub fn create_router(app_state: Arc<AppState>) -> Router {
                 .delete(remove_user_wishlist)
                 .patch(remove_wishlist_record),
         )
+        .route(
+            "/records/wishlist/{user_id}/{record_id}",
+            patch(edit_wishlist_record),
+        )
+        .route(
+            "/users/{id}/wishlist_share",
+            get(get_wishlist_share)
+                .post(create_wishlist_share)
+                .delete(revoke_wishlist_share),
+        )
+        .route("/wishlists/{share_token}", get(get_shared_wishlist))
+        .route("/wishlists/{share_token}/reservations", post(reserve_gift))
+        .route(
+            "/wishlists/{share_token}/reservations/{cancel_token}",
+            delete(cancel_gift_reservation),
+        )
+        .route("/users/{id}/alerts", get(get_user_alerts))
+        .route("/users/{id}/alerts/read", post(mark_alerts_read))
+        .route("/users/{id}/notifications", get(get_user_notifications))
+        .route(
+            "/users/{id}/notifications/read",
+            post(mark_notifications_read),
+        )
+        .route(
+            "/users/{id}/notifications/{notification_id}/read",
+            post(mark_notification_read),
+        )
+        .route(
+            "/users/{id}/notification_preferences",
+            get(get_notification_preferences).patch(edit_notification_preferences),
+        )
+        .route("/users/{id}/events", get(get_user_events))
+        .route("/feed", get(get_feed))
+        .route("/users/{id}/activity", get(get_user_activity))
+        .route("/users/{id}/follows", get(get_following).post(follow_user))
+        .route("/users/{id}/follows/{followed_id}", delete(unfollow_user))
+        .route("/users/{id}/followers", get(get_followers))
+        .route(
+            "/users/{id}/conversations",
+            get(get_conversations).post(start_conversation),
+        )
+        .route(
+            "/users/{id}/conversations/{conversation_id}/messages",
+            get(get_conversation_messages).post(send_message),
+        )
+        .route(
+            "/users/{id}/conversations/{conversation_id}/read",
+            post(mark_conversation_read),
+        )
+        .route(
+            "/users/{id}/blocks",
+            get(get_blocked_users).post(block_user),
+        )
+        .route("/users/{id}/blocks/{blocked_id}", delete(unblock_user))
+        .route("/users/{id}/plays", get(get_user_plays).post(log_play))
+        .route("/users/{id}/plays/counts", get(get_play_counts))
+        .route("/users/{id}/plays/neglected", get(get_neglected_records))
+        .route("/users/{id}/plays/listening_time", get(get_listening_time))
+        .route("/users/{id}/plays/{play_id}", delete(delete_play))
+        .route("/users/{id}/loans", get(get_user_loans).post(lend_record))
+        .route("/users/{id}/loans/overdue", get(get_overdue_loans))
+        .route("/users/{id}/loans/{loan_id}", patch(edit_loan))
+        .route("/users/{id}/loans/{loan_id}/return", post(return_loan))
+        .route("/users/{id}/recommendations", get(get_user_recommendations))
+        .route(
+            "/users/{id}/lists",
+            get(get_user_lists).post(create_record_list),
+        )
+        .route(
+            "/lists/{list_id}",
+            get(get_record_list)
+                .patch(edit_record_list)
+                .delete(delete_record_list),
+        )
+        .route("/lists/{list_id}/records", post(add_list_record))
+        .route(
+            "/lists/{list_id}/records/{record_id}",
+            delete(remove_list_record),
+        )
+        .route("/lists/{list_id}/order", put(reorder_record_list))
+        .route("/lists/{list_id}/copy", post(copy_record_list))
+        .route(
+            "/records/{id}/comments",
+            get(get_record_comments).post(create_record_comment),
+        )
+        .route(
+            "/stores/{id}/comments",
+            get(get_store_comments).post(create_store_comment),
+        )
+        .route(
+            "/users/{id}/collection/comments",
+            get(get_collection_comments).post(create_collection_comment),
+        )
+        .route(
+            "/comments/{comment_id}",
+            patch(edit_comment).delete(delete_comment),
+        )
+        .route("/comments/{comment_id}/remove", post(remove_comment))
+        .route("/comments/{comment_id}/reports", post(report_comment))
+        .route("/users/{id}/dig_trip", get(plan_dig_trip))
+        .route("/users/{id}/events.ics", get(get_user_events_ics))
         .route(
             "/record_stores/{user_id}",
             get(get_user_record_stores)
                 .post(add_user_record_store)
                 .put(add_existing_record_store)
                 .delete(delete_user_record_store),
+        )
+        // catalog moderation
+        .route(
+            "/moderation/records/duplicates",
+            get(find_duplicate_records),
+        )
+        .route("/moderation/records/merge", post(merge_records))
+        .route("/moderation/suggestions", get(list_suggestions))
+        .route(
+            "/moderation/suggestions/{id}/approve",
+            post(approve_suggestion),
+        )
+        .route(
+            "/moderation/suggestions/{id}/reject",
+            post(reject_suggestion),
+        )
+        .route("/users/{id}/suggestions", get(get_user_suggestions))
+        // recommendations
+        .route(
+            "/moderation/recommendations/refresh",
+            post(refresh_recommendations),
+        )
+        // store ownership
+        .route("/moderation/store_claims", get(list_store_claims))
+        .route(
+            "/moderation/store_claims/{id}/verify",
+            post(verify_store_claim),
+        )
+        .route(
+            "/moderation/store_claims/{id}/reject",
+            post(reject_store_claim),
+        )
+        .route("/users/{id}/store_claims", get(get_user_store_claims))
+        // comment reports
+        .route("/moderation/comment_reports", get(list_comment_reports))
+        .route(
+            "/moderation/comment_reports/{id}/resolve",
+            post(resolve_comment_report),
         );
 
     // return the router